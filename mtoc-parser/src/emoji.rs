@@ -0,0 +1,2250 @@
+// Copyright 2019 Fletcher Nichol and/or applicable contributors.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license (see <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
+// distributed except according to those terms.
+
+//! Emoji shortcodes and presentation data.
+//!
+//! The shortcodes are those of GitHub's [gemoji] database (v4.1.0), which GitHub and GitLab both
+//! use, and the emoji are those of the [Unicode emoji] specification (v16.0).
+//!
+//! [gemoji]: https://github.com/github/gemoji
+//! [Unicode emoji]: https://unicode.org/reports/tr51/
+
+use std::cmp::Ordering;
+
+/// Returns the emoji named by a shortcode, given without its surrounding colons.
+pub(crate) fn get_by_shortcode(shortcode: &str) -> Option<&'static str> {
+    SHORTCODES
+        .binary_search_by(|&(code, _)| code.cmp(shortcode))
+        .ok()
+        .map(|i| SHORTCODES[i].1)
+}
+
+/// Returns whether a character is an emoji which is displayed as an emoji by default.
+pub(crate) fn is_emoji_presentation(c: char) -> bool {
+    in_ranges(EMOJI_PRESENTATION, c)
+}
+
+/// Returns whether a character is an emoji which is displayed as text by default, and so is only
+/// displayed as an emoji when followed by an emoji variation selector (such as `©`).
+pub(crate) fn is_text_presentation(c: char) -> bool {
+    in_ranges(TEXT_PRESENTATION, c)
+}
+
+fn in_ranges(ranges: &[(char, char)], c: char) -> bool {
+    ranges
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                Ordering::Less
+            } else if start > c {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Shortcodes and the emoji they name, sorted by shortcode.
+const SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("1234", "🔢"),
+    ("1st_place_medal", "🥇"),
+    ("2nd_place_medal", "🥈"),
+    ("3rd_place_medal", "🥉"),
+    ("8ball", "🎱"),
+    ("a", "🅰\u{fe0f}"),
+    ("ab", "🆎"),
+    ("abacus", "🧮"),
+    ("abc", "🔤"),
+    ("abcd", "🔡"),
+    ("accept", "🉑"),
+    ("accordion", "🪗"),
+    ("adhesive_bandage", "🩹"),
+    ("adult", "🧑"),
+    ("aerial_tramway", "🚡"),
+    ("afghanistan", "🇦🇫"),
+    ("airplane", "✈\u{fe0f}"),
+    ("aland_islands", "🇦🇽"),
+    ("alarm_clock", "⏰"),
+    ("albania", "🇦🇱"),
+    ("alembic", "⚗\u{fe0f}"),
+    ("algeria", "🇩🇿"),
+    ("alien", "👽"),
+    ("ambulance", "🚑"),
+    ("american_samoa", "🇦🇸"),
+    ("amphora", "🏺"),
+    ("anatomical_heart", "🫀"),
+    ("anchor", "⚓"),
+    ("andorra", "🇦🇩"),
+    ("angel", "👼"),
+    ("anger", "💢"),
+    ("angola", "🇦🇴"),
+    ("angry", "😠"),
+    ("anguilla", "🇦🇮"),
+    ("anguished", "😧"),
+    ("ant", "🐜"),
+    ("antarctica", "🇦🇶"),
+    ("antigua_barbuda", "🇦🇬"),
+    ("apple", "🍎"),
+    ("aquarius", "♒"),
+    ("argentina", "🇦🇷"),
+    ("aries", "♈"),
+    ("armenia", "🇦🇲"),
+    ("arrow_backward", "◀\u{fe0f}"),
+    ("arrow_double_down", "⏬"),
+    ("arrow_double_up", "⏫"),
+    ("arrow_down", "⬇\u{fe0f}"),
+    ("arrow_down_small", "🔽"),
+    ("arrow_forward", "▶\u{fe0f}"),
+    ("arrow_heading_down", "⤵\u{fe0f}"),
+    ("arrow_heading_up", "⤴\u{fe0f}"),
+    ("arrow_left", "⬅\u{fe0f}"),
+    ("arrow_lower_left", "↙\u{fe0f}"),
+    ("arrow_lower_right", "↘\u{fe0f}"),
+    ("arrow_right", "➡\u{fe0f}"),
+    ("arrow_right_hook", "↪\u{fe0f}"),
+    ("arrow_up", "⬆\u{fe0f}"),
+    ("arrow_up_down", "↕\u{fe0f}"),
+    ("arrow_up_small", "🔼"),
+    ("arrow_upper_left", "↖\u{fe0f}"),
+    ("arrow_upper_right", "↗\u{fe0f}"),
+    ("arrows_clockwise", "🔃"),
+    ("arrows_counterclockwise", "🔄"),
+    ("art", "🎨"),
+    ("articulated_lorry", "🚛"),
+    ("artificial_satellite", "🛰\u{fe0f}"),
+    ("artist", "🧑\u{200d}🎨"),
+    ("aruba", "🇦🇼"),
+    ("ascension_island", "🇦🇨"),
+    ("asterisk", "*\u{fe0f}\u{20e3}"),
+    ("astonished", "😲"),
+    ("astronaut", "🧑\u{200d}🚀"),
+    ("athletic_shoe", "👟"),
+    ("atm", "🏧"),
+    ("atom_symbol", "⚛\u{fe0f}"),
+    ("australia", "🇦🇺"),
+    ("austria", "🇦🇹"),
+    ("auto_rickshaw", "🛺"),
+    ("avocado", "🥑"),
+    ("axe", "🪓"),
+    ("azerbaijan", "🇦🇿"),
+    ("b", "🅱\u{fe0f}"),
+    ("baby", "👶"),
+    ("baby_bottle", "🍼"),
+    ("baby_chick", "🐤"),
+    ("baby_symbol", "🚼"),
+    ("back", "🔙"),
+    ("bacon", "🥓"),
+    ("badger", "🦡"),
+    ("badminton", "🏸"),
+    ("bagel", "🥯"),
+    ("baggage_claim", "🛄"),
+    ("baguette_bread", "🥖"),
+    ("bahamas", "🇧🇸"),
+    ("bahrain", "🇧🇭"),
+    ("balance_scale", "⚖\u{fe0f}"),
+    ("bald_man", "👨\u{200d}🦲"),
+    ("bald_woman", "👩\u{200d}🦲"),
+    ("ballet_shoes", "🩰"),
+    ("balloon", "🎈"),
+    ("ballot_box", "🗳\u{fe0f}"),
+    ("ballot_box_with_check", "☑\u{fe0f}"),
+    ("bamboo", "🎍"),
+    ("banana", "🍌"),
+    ("bangbang", "‼\u{fe0f}"),
+    ("bangladesh", "🇧🇩"),
+    ("banjo", "🪕"),
+    ("bank", "🏦"),
+    ("bar_chart", "📊"),
+    ("barbados", "🇧🇧"),
+    ("barber", "💈"),
+    ("baseball", "⚾"),
+    ("basket", "🧺"),
+    ("basketball", "🏀"),
+    ("basketball_man", "⛹\u{fe0f}\u{200d}♂\u{fe0f}"),
+    ("basketball_woman", "⛹\u{fe0f}\u{200d}♀\u{fe0f}"),
+    ("bat", "🦇"),
+    ("bath", "🛀"),
+    ("bathtub", "🛁"),
+    ("battery", "🔋"),
+    ("beach_umbrella", "🏖\u{fe0f}"),
+    ("beans", "🫘"),
+    ("bear", "🐻"),
+    ("bearded_person", "🧔"),
+    ("beaver", "🦫"),
+    ("bed", "🛏\u{fe0f}"),
+    ("bee", "🐝"),
+    ("beer", "🍺"),
+    ("beers", "🍻"),
+    ("beetle", "🪲"),
+    ("beginner", "🔰"),
+    ("belarus", "🇧🇾"),
+    ("belgium", "🇧🇪"),
+    ("belize", "🇧🇿"),
+    ("bell", "🔔"),
+    ("bell_pepper", "🫑"),
+    ("bellhop_bell", "🛎\u{fe0f}"),
+    ("benin", "🇧🇯"),
+    ("bento", "🍱"),
+    ("bermuda", "🇧🇲"),
+    ("beverage_box", "🧃"),
+    ("bhutan", "🇧🇹"),
+    ("bicyclist", "🚴"),
+    ("bike", "🚲"),
+    ("biking_man", "🚴\u{200d}♂\u{fe0f}"),
+    ("biking_woman", "🚴\u{200d}♀\u{fe0f}"),
+    ("bikini", "👙"),
+    ("billed_cap", "🧢"),
+    ("biohazard", "☣\u{fe0f}"),
+    ("bird", "🐦"),
+    ("birthday", "🎂"),
+    ("bison", "🦬"),
+    ("biting_lip", "🫦"),
+    ("black_bird", "🐦\u{200d}⬛"),
+    ("black_cat", "🐈\u{200d}⬛"),
+    ("black_circle", "⚫"),
+    ("black_flag", "🏴"),
+    ("black_heart", "🖤"),
+    ("black_joker", "🃏"),
+    ("black_large_square", "⬛"),
+    ("black_medium_small_square", "◾"),
+    ("black_medium_square", "◼\u{fe0f}"),
+    ("black_nib", "✒\u{fe0f}"),
+    ("black_small_square", "▪\u{fe0f}"),
+    ("black_square_button", "🔲"),
+    ("blond_haired_man", "👱\u{200d}♂\u{fe0f}"),
+    ("blond_haired_person", "👱"),
+    ("blond_haired_woman", "👱\u{200d}♀\u{fe0f}"),
+    ("blonde_woman", "👱\u{200d}♀\u{fe0f}"),
+    ("blossom", "🌼"),
+    ("blowfish", "🐡"),
+    ("blue_book", "📘"),
+    ("blue_car", "🚙"),
+    ("blue_heart", "💙"),
+    ("blue_square", "🟦"),
+    ("blueberries", "🫐"),
+    ("blush", "😊"),
+    ("boar", "🐗"),
+    ("boat", "⛵"),
+    ("bolivia", "🇧🇴"),
+    ("bomb", "💣"),
+    ("bone", "🦴"),
+    ("book", "📖"),
+    ("bookmark", "🔖"),
+    ("bookmark_tabs", "📑"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("boomerang", "🪃"),
+    ("boot", "👢"),
+    ("bosnia_herzegovina", "🇧🇦"),
+    ("botswana", "🇧🇼"),
+    ("bouncing_ball_man", "⛹\u{fe0f}\u{200d}♂\u{fe0f}"),
+    ("bouncing_ball_person", "⛹\u{fe0f}"),
+    ("bouncing_ball_woman", "⛹\u{fe0f}\u{200d}♀\u{fe0f}"),
+    ("bouquet", "💐"),
+    ("bouvet_island", "🇧🇻"),
+    ("bow", "🙇"),
+    ("bow_and_arrow", "🏹"),
+    ("bowing_man", "🙇\u{200d}♂\u{fe0f}"),
+    ("bowing_woman", "🙇\u{200d}♀\u{fe0f}"),
+    ("bowl_with_spoon", "🥣"),
+    ("bowling", "🎳"),
+    ("boxing_glove", "🥊"),
+    ("boy", "👦"),
+    ("brain", "🧠"),
+    ("brazil", "🇧🇷"),
+    ("bread", "🍞"),
+    ("breast_feeding", "🤱"),
+    ("bricks", "🧱"),
+    ("bride_with_veil", "👰\u{200d}♀\u{fe0f}"),
+    ("bridge_at_night", "🌉"),
+    ("briefcase", "💼"),
+    ("british_indian_ocean_territory", "🇮🇴"),
+    ("british_virgin_islands", "🇻🇬"),
+    ("broccoli", "🥦"),
+    ("broken_heart", "💔"),
+    ("broom", "🧹"),
+    ("brown_circle", "🟤"),
+    ("brown_heart", "🤎"),
+    ("brown_square", "🟫"),
+    ("brunei", "🇧🇳"),
+    ("bubble_tea", "🧋"),
+    ("bubbles", "🫧"),
+    ("bucket", "🪣"),
+    ("bug", "🐛"),
+    ("building_construction", "🏗\u{fe0f}"),
+    ("bulb", "💡"),
+    ("bulgaria", "🇧🇬"),
+    ("bullettrain_front", "🚅"),
+    ("bullettrain_side", "🚄"),
+    ("burkina_faso", "🇧🇫"),
+    ("burrito", "🌯"),
+    ("burundi", "🇧🇮"),
+    ("bus", "🚌"),
+    ("business_suit_levitating", "🕴\u{fe0f}"),
+    ("busstop", "🚏"),
+    ("bust_in_silhouette", "👤"),
+    ("busts_in_silhouette", "👥"),
+    ("butter", "🧈"),
+    ("butterfly", "🦋"),
+    ("cactus", "🌵"),
+    ("cake", "🍰"),
+    ("calendar", "📆"),
+    ("call_me_hand", "🤙"),
+    ("calling", "📲"),
+    ("cambodia", "🇰🇭"),
+    ("camel", "🐫"),
+    ("camera", "📷"),
+    ("camera_flash", "📸"),
+    ("cameroon", "🇨🇲"),
+    ("camping", "🏕\u{fe0f}"),
+    ("canada", "🇨🇦"),
+    ("canary_islands", "🇮🇨"),
+    ("cancer", "♋"),
+    ("candle", "🕯\u{fe0f}"),
+    ("candy", "🍬"),
+    ("canned_food", "🥫"),
+    ("canoe", "🛶"),
+    ("cape_verde", "🇨🇻"),
+    ("capital_abcd", "🔠"),
+    ("capricorn", "♑"),
+    ("car", "🚗"),
+    ("card_file_box", "🗃\u{fe0f}"),
+    ("card_index", "📇"),
+    ("card_index_dividers", "🗂\u{fe0f}"),
+    ("caribbean_netherlands", "🇧🇶"),
+    ("carousel_horse", "🎠"),
+    ("carpentry_saw", "🪚"),
+    ("carrot", "🥕"),
+    ("cartwheeling", "🤸"),
+    ("cat", "🐱"),
+    ("cat2", "🐈"),
+    ("cayman_islands", "🇰🇾"),
+    ("cd", "💿"),
+    ("central_african_republic", "🇨🇫"),
+    ("ceuta_melilla", "🇪🇦"),
+    ("chad", "🇹🇩"),
+    ("chains", "⛓\u{fe0f}"),
+    ("chair", "🪑"),
+    ("champagne", "🍾"),
+    ("chart", "💹"),
+    ("chart_with_downwards_trend", "📉"),
+    ("chart_with_upwards_trend", "📈"),
+    ("checkered_flag", "🏁"),
+    ("cheese", "🧀"),
+    ("cherries", "🍒"),
+    ("cherry_blossom", "🌸"),
+    ("chess_pawn", "♟\u{fe0f}"),
+    ("chestnut", "🌰"),
+    ("chicken", "🐔"),
+    ("child", "🧒"),
+    ("children_crossing", "🚸"),
+    ("chile", "🇨🇱"),
+    ("chipmunk", "🐿\u{fe0f}"),
+    ("chocolate_bar", "🍫"),
+    ("chopsticks", "🥢"),
+    ("christmas_island", "🇨🇽"),
+    ("christmas_tree", "🎄"),
+    ("church", "⛪"),
+    ("cinema", "🎦"),
+    ("circus_tent", "🎪"),
+    ("city_sunrise", "🌇"),
+    ("city_sunset", "🌆"),
+    ("cityscape", "🏙\u{fe0f}"),
+    ("cl", "🆑"),
+    ("clamp", "🗜\u{fe0f}"),
+    ("clap", "👏"),
+    ("clapper", "🎬"),
+    ("classical_building", "🏛\u{fe0f}"),
+    ("climbing", "🧗"),
+    ("climbing_man", "🧗\u{200d}♂\u{fe0f}"),
+    ("climbing_woman", "🧗\u{200d}♀\u{fe0f}"),
+    ("clinking_glasses", "🥂"),
+    ("clipboard", "📋"),
+    ("clipperton_island", "🇨🇵"),
+    ("clock1", "🕐"),
+    ("clock10", "🕙"),
+    ("clock1030", "🕥"),
+    ("clock11", "🕚"),
+    ("clock1130", "🕦"),
+    ("clock12", "🕛"),
+    ("clock1230", "🕧"),
+    ("clock130", "🕜"),
+    ("clock2", "🕑"),
+    ("clock230", "🕝"),
+    ("clock3", "🕒"),
+    ("clock330", "🕞"),
+    ("clock4", "🕓"),
+    ("clock430", "🕟"),
+    ("clock5", "🕔"),
+    ("clock530", "🕠"),
+    ("clock6", "🕕"),
+    ("clock630", "🕡"),
+    ("clock7", "🕖"),
+    ("clock730", "🕢"),
+    ("clock8", "🕗"),
+    ("clock830", "🕣"),
+    ("clock9", "🕘"),
+    ("clock930", "🕤"),
+    ("closed_book", "📕"),
+    ("closed_lock_with_key", "🔐"),
+    ("closed_umbrella", "🌂"),
+    ("cloud", "☁\u{fe0f}"),
+    ("cloud_with_lightning", "🌩\u{fe0f}"),
+    ("cloud_with_lightning_and_rain", "⛈\u{fe0f}"),
+    ("cloud_with_rain", "🌧\u{fe0f}"),
+    ("cloud_with_snow", "🌨\u{fe0f}"),
+    ("clown_face", "🤡"),
+    ("clubs", "♣\u{fe0f}"),
+    ("cn", "🇨🇳"),
+    ("coat", "🧥"),
+    ("cockroach", "🪳"),
+    ("cocktail", "🍸"),
+    ("coconut", "🥥"),
+    ("cocos_islands", "🇨🇨"),
+    ("coffee", "☕"),
+    ("coffin", "⚰\u{fe0f}"),
+    ("coin", "🪙"),
+    ("cold_face", "🥶"),
+    ("cold_sweat", "😰"),
+    ("collision", "💥"),
+    ("colombia", "🇨🇴"),
+    ("comet", "☄\u{fe0f}"),
+    ("comoros", "🇰🇲"),
+    ("compass", "🧭"),
+    ("computer", "💻"),
+    ("computer_mouse", "🖱\u{fe0f}"),
+    ("confetti_ball", "🎊"),
+    ("confounded", "😖"),
+    ("confused", "😕"),
+    ("congo_brazzaville", "🇨🇬"),
+    ("congo_kinshasa", "🇨🇩"),
+    ("congratulations", "㊗\u{fe0f}"),
+    ("construction", "🚧"),
+    ("construction_worker", "👷"),
+    ("construction_worker_man", "👷\u{200d}♂\u{fe0f}"),
+    ("construction_worker_woman", "👷\u{200d}♀\u{fe0f}"),
+    ("control_knobs", "🎛\u{fe0f}"),
+    ("convenience_store", "🏪"),
+    ("cook", "🧑\u{200d}🍳"),
+    ("cook_islands", "🇨🇰"),
+    ("cookie", "🍪"),
+    ("cool", "🆒"),
+    ("cop", "👮"),
+    ("copyright", "©\u{fe0f}"),
+    ("coral", "🪸"),
+    ("corn", "🌽"),
+    ("costa_rica", "🇨🇷"),
+    ("cote_divoire", "🇨🇮"),
+    ("couch_and_lamp", "🛋\u{fe0f}"),
+    ("couple", "👫"),
+    ("couple_with_heart", "💑"),
+    ("couple_with_heart_man_man", "👨\u{200d}❤\u{fe0f}\u{200d}👨"),
+    (
+        "couple_with_heart_woman_man",
+        "👩\u{200d}❤\u{fe0f}\u{200d}👨",
+    ),
+    (
+        "couple_with_heart_woman_woman",
+        "👩\u{200d}❤\u{fe0f}\u{200d}👩",
+    ),
+    ("couplekiss", "💏"),
+    (
+        "couplekiss_man_man",
+        "👨\u{200d}❤\u{fe0f}\u{200d}💋\u{200d}👨",
+    ),
+    (
+        "couplekiss_man_woman",
+        "👩\u{200d}❤\u{fe0f}\u{200d}💋\u{200d}👨",
+    ),
+    (
+        "couplekiss_woman_woman",
+        "👩\u{200d}❤\u{fe0f}\u{200d}💋\u{200d}👩",
+    ),
+    ("cow", "🐮"),
+    ("cow2", "🐄"),
+    ("cowboy_hat_face", "🤠"),
+    ("crab", "🦀"),
+    ("crayon", "🖍\u{fe0f}"),
+    ("credit_card", "💳"),
+    ("crescent_moon", "🌙"),
+    ("cricket", "🦗"),
+    ("cricket_game", "🏏"),
+    ("croatia", "🇭🇷"),
+    ("crocodile", "🐊"),
+    ("croissant", "🥐"),
+    ("crossed_fingers", "🤞"),
+    ("crossed_flags", "🎌"),
+    ("crossed_swords", "⚔\u{fe0f}"),
+    ("crown", "👑"),
+    ("crutch", "🩼"),
+    ("cry", "😢"),
+    ("crying_cat_face", "😿"),
+    ("crystal_ball", "🔮"),
+    ("cuba", "🇨🇺"),
+    ("cucumber", "🥒"),
+    ("cup_with_straw", "🥤"),
+    ("cupcake", "🧁"),
+    ("cupid", "💘"),
+    ("curacao", "🇨🇼"),
+    ("curling_stone", "🥌"),
+    ("curly_haired_man", "👨\u{200d}🦱"),
+    ("curly_haired_woman", "👩\u{200d}🦱"),
+    ("curly_loop", "➰"),
+    ("currency_exchange", "💱"),
+    ("curry", "🍛"),
+    ("cursing_face", "🤬"),
+    ("custard", "🍮"),
+    ("customs", "🛃"),
+    ("cut_of_meat", "🥩"),
+    ("cyclone", "🌀"),
+    ("cyprus", "🇨🇾"),
+    ("czech_republic", "🇨🇿"),
+    ("dagger", "🗡\u{fe0f}"),
+    ("dancer", "💃"),
+    ("dancers", "👯"),
+    ("dancing_men", "👯\u{200d}♂\u{fe0f}"),
+    ("dancing_women", "👯\u{200d}♀\u{fe0f}"),
+    ("dango", "🍡"),
+    ("dark_sunglasses", "🕶\u{fe0f}"),
+    ("dart", "🎯"),
+    ("dash", "💨"),
+    ("date", "📅"),
+    ("de", "🇩🇪"),
+    ("deaf_man", "🧏\u{200d}♂\u{fe0f}"),
+    ("deaf_person", "🧏"),
+    ("deaf_woman", "🧏\u{200d}♀\u{fe0f}"),
+    ("deciduous_tree", "🌳"),
+    ("deer", "🦌"),
+    ("denmark", "🇩🇰"),
+    ("department_store", "🏬"),
+    ("derelict_house", "🏚\u{fe0f}"),
+    ("desert", "🏜\u{fe0f}"),
+    ("desert_island", "🏝\u{fe0f}"),
+    ("desktop_computer", "🖥\u{fe0f}"),
+    ("detective", "🕵\u{fe0f}"),
+    ("diamond_shape_with_a_dot_inside", "💠"),
+    ("diamonds", "♦\u{fe0f}"),
+    ("diego_garcia", "🇩🇬"),
+    ("disappointed", "😞"),
+    ("disappointed_relieved", "😥"),
+    ("disguised_face", "🥸"),
+    ("diving_mask", "🤿"),
+    ("diya_lamp", "🪔"),
+    ("dizzy", "💫"),
+    ("dizzy_face", "😵"),
+    ("djibouti", "🇩🇯"),
+    ("dna", "🧬"),
+    ("do_not_litter", "🚯"),
+    ("dodo", "🦤"),
+    ("dog", "🐶"),
+    ("dog2", "🐕"),
+    ("dollar", "💵"),
+    ("dolls", "🎎"),
+    ("dolphin", "🐬"),
+    ("dominica", "🇩🇲"),
+    ("dominican_republic", "🇩🇴"),
+    ("donkey", "🫏"),
+    ("door", "🚪"),
+    ("dotted_line_face", "🫥"),
+    ("doughnut", "🍩"),
+    ("dove", "🕊\u{fe0f}"),
+    ("dragon", "🐉"),
+    ("dragon_face", "🐲"),
+    ("dress", "👗"),
+    ("dromedary_camel", "🐪"),
+    ("drooling_face", "🤤"),
+    ("drop_of_blood", "🩸"),
+    ("droplet", "💧"),
+    ("drum", "🥁"),
+    ("duck", "🦆"),
+    ("dumpling", "🥟"),
+    ("dvd", "📀"),
+    ("e-mail", "📧"),
+    ("eagle", "🦅"),
+    ("ear", "👂"),
+    ("ear_of_rice", "🌾"),
+    ("ear_with_hearing_aid", "🦻"),
+    ("earth_africa", "🌍"),
+    ("earth_americas", "🌎"),
+    ("earth_asia", "🌏"),
+    ("ecuador", "🇪🇨"),
+    ("egg", "🥚"),
+    ("eggplant", "🍆"),
+    ("egypt", "🇪🇬"),
+    ("eight", "8\u{fe0f}\u{20e3}"),
+    ("eight_pointed_black_star", "✴\u{fe0f}"),
+    ("eight_spoked_asterisk", "✳\u{fe0f}"),
+    ("eject_button", "⏏\u{fe0f}"),
+    ("el_salvador", "🇸🇻"),
+    ("electric_plug", "🔌"),
+    ("elephant", "🐘"),
+    ("elevator", "🛗"),
+    ("elf", "🧝"),
+    ("elf_man", "🧝\u{200d}♂\u{fe0f}"),
+    ("elf_woman", "🧝\u{200d}♀\u{fe0f}"),
+    ("email", "📧"),
+    ("empty_nest", "🪹"),
+    ("end", "🔚"),
+    (
+        "england",
+        "🏴\u{e0067}\u{e0062}\u{e0065}\u{e006e}\u{e0067}\u{e007f}",
+    ),
+    ("envelope", "✉\u{fe0f}"),
+    ("envelope_with_arrow", "📩"),
+    ("equatorial_guinea", "🇬🇶"),
+    ("eritrea", "🇪🇷"),
+    ("es", "🇪🇸"),
+    ("estonia", "🇪🇪"),
+    ("ethiopia", "🇪🇹"),
+    ("eu", "🇪🇺"),
+    ("euro", "💶"),
+    ("european_castle", "🏰"),
+    ("european_post_office", "🏤"),
+    ("european_union", "🇪🇺"),
+    ("evergreen_tree", "🌲"),
+    ("exclamation", "❗"),
+    ("exploding_head", "🤯"),
+    ("expressionless", "😑"),
+    ("eye", "👁\u{fe0f}"),
+    ("eye_speech_bubble", "👁\u{fe0f}\u{200d}🗨\u{fe0f}"),
+    ("eyeglasses", "👓"),
+    ("eyes", "👀"),
+    ("face_exhaling", "😮\u{200d}💨"),
+    ("face_holding_back_tears", "🥹"),
+    ("face_in_clouds", "😶\u{200d}🌫\u{fe0f}"),
+    ("face_with_diagonal_mouth", "🫤"),
+    ("face_with_head_bandage", "🤕"),
+    ("face_with_open_eyes_and_hand_over_mouth", "🫢"),
+    ("face_with_peeking_eye", "🫣"),
+    ("face_with_spiral_eyes", "😵\u{200d}💫"),
+    ("face_with_thermometer", "🤒"),
+    ("facepalm", "🤦"),
+    ("facepunch", "👊"),
+    ("factory", "🏭"),
+    ("factory_worker", "🧑\u{200d}🏭"),
+    ("fairy", "🧚"),
+    ("fairy_man", "🧚\u{200d}♂\u{fe0f}"),
+    ("fairy_woman", "🧚\u{200d}♀\u{fe0f}"),
+    ("falafel", "🧆"),
+    ("falkland_islands", "🇫🇰"),
+    ("fallen_leaf", "🍂"),
+    ("family", "👪"),
+    ("family_man_boy", "👨\u{200d}👦"),
+    ("family_man_boy_boy", "👨\u{200d}👦\u{200d}👦"),
+    ("family_man_girl", "👨\u{200d}👧"),
+    ("family_man_girl_boy", "👨\u{200d}👧\u{200d}👦"),
+    ("family_man_girl_girl", "👨\u{200d}👧\u{200d}👧"),
+    ("family_man_man_boy", "👨\u{200d}👨\u{200d}👦"),
+    ("family_man_man_boy_boy", "👨\u{200d}👨\u{200d}👦\u{200d}👦"),
+    ("family_man_man_girl", "👨\u{200d}👨\u{200d}👧"),
+    (
+        "family_man_man_girl_boy",
+        "👨\u{200d}👨\u{200d}👧\u{200d}👦",
+    ),
+    (
+        "family_man_man_girl_girl",
+        "👨\u{200d}👨\u{200d}👧\u{200d}👧",
+    ),
+    ("family_man_woman_boy", "👨\u{200d}👩\u{200d}👦"),
+    (
+        "family_man_woman_boy_boy",
+        "👨\u{200d}👩\u{200d}👦\u{200d}👦",
+    ),
+    ("family_man_woman_girl", "👨\u{200d}👩\u{200d}👧"),
+    (
+        "family_man_woman_girl_boy",
+        "👨\u{200d}👩\u{200d}👧\u{200d}👦",
+    ),
+    (
+        "family_man_woman_girl_girl",
+        "👨\u{200d}👩\u{200d}👧\u{200d}👧",
+    ),
+    ("family_woman_boy", "👩\u{200d}👦"),
+    ("family_woman_boy_boy", "👩\u{200d}👦\u{200d}👦"),
+    ("family_woman_girl", "👩\u{200d}👧"),
+    ("family_woman_girl_boy", "👩\u{200d}👧\u{200d}👦"),
+    ("family_woman_girl_girl", "👩\u{200d}👧\u{200d}👧"),
+    ("family_woman_woman_boy", "👩\u{200d}👩\u{200d}👦"),
+    (
+        "family_woman_woman_boy_boy",
+        "👩\u{200d}👩\u{200d}👦\u{200d}👦",
+    ),
+    ("family_woman_woman_girl", "👩\u{200d}👩\u{200d}👧"),
+    (
+        "family_woman_woman_girl_boy",
+        "👩\u{200d}👩\u{200d}👧\u{200d}👦",
+    ),
+    (
+        "family_woman_woman_girl_girl",
+        "👩\u{200d}👩\u{200d}👧\u{200d}👧",
+    ),
+    ("farmer", "🧑\u{200d}🌾"),
+    ("faroe_islands", "🇫🇴"),
+    ("fast_forward", "⏩"),
+    ("fax", "📠"),
+    ("fearful", "😨"),
+    ("feather", "🪶"),
+    ("feet", "🐾"),
+    ("female_detective", "🕵\u{fe0f}\u{200d}♀\u{fe0f}"),
+    ("female_sign", "♀\u{fe0f}"),
+    ("ferris_wheel", "🎡"),
+    ("ferry", "⛴\u{fe0f}"),
+    ("field_hockey", "🏑"),
+    ("fiji", "🇫🇯"),
+    ("file_cabinet", "🗄\u{fe0f}"),
+    ("file_folder", "📁"),
+    ("film_projector", "📽\u{fe0f}"),
+    ("film_strip", "🎞\u{fe0f}"),
+    ("finland", "🇫🇮"),
+    ("fire", "🔥"),
+    ("fire_engine", "🚒"),
+    ("fire_extinguisher", "🧯"),
+    ("firecracker", "🧨"),
+    ("firefighter", "🧑\u{200d}🚒"),
+    ("fireworks", "🎆"),
+    ("first_quarter_moon", "🌓"),
+    ("first_quarter_moon_with_face", "🌛"),
+    ("fish", "🐟"),
+    ("fish_cake", "🍥"),
+    ("fishing_pole_and_fish", "🎣"),
+    ("fist", "✊"),
+    ("fist_left", "🤛"),
+    ("fist_oncoming", "👊"),
+    ("fist_raised", "✊"),
+    ("fist_right", "🤜"),
+    ("five", "5\u{fe0f}\u{20e3}"),
+    ("flags", "🎏"),
+    ("flamingo", "🦩"),
+    ("flashlight", "🔦"),
+    ("flat_shoe", "🥿"),
+    ("flatbread", "🫓"),
+    ("fleur_de_lis", "⚜\u{fe0f}"),
+    ("flight_arrival", "🛬"),
+    ("flight_departure", "🛫"),
+    ("flipper", "🐬"),
+    ("floppy_disk", "💾"),
+    ("flower_playing_cards", "🎴"),
+    ("flushed", "😳"),
+    ("flute", "🪈"),
+    ("fly", "🪰"),
+    ("flying_disc", "🥏"),
+    ("flying_saucer", "🛸"),
+    ("fog", "🌫\u{fe0f}"),
+    ("foggy", "🌁"),
+    ("folding_hand_fan", "🪭"),
+    ("fondue", "🫕"),
+    ("foot", "🦶"),
+    ("football", "🏈"),
+    ("footprints", "👣"),
+    ("fork_and_knife", "🍴"),
+    ("fortune_cookie", "🥠"),
+    ("fountain", "⛲"),
+    ("fountain_pen", "🖋\u{fe0f}"),
+    ("four", "4\u{fe0f}\u{20e3}"),
+    ("four_leaf_clover", "🍀"),
+    ("fox_face", "🦊"),
+    ("fr", "🇫🇷"),
+    ("framed_picture", "🖼\u{fe0f}"),
+    ("free", "🆓"),
+    ("french_guiana", "🇬🇫"),
+    ("french_polynesia", "🇵🇫"),
+    ("french_southern_territories", "🇹🇫"),
+    ("fried_egg", "🍳"),
+    ("fried_shrimp", "🍤"),
+    ("fries", "🍟"),
+    ("frog", "🐸"),
+    ("frowning", "😦"),
+    ("frowning_face", "☹\u{fe0f}"),
+    ("frowning_man", "🙍\u{200d}♂\u{fe0f}"),
+    ("frowning_person", "🙍"),
+    ("frowning_woman", "🙍\u{200d}♀\u{fe0f}"),
+    ("fu", "🖕"),
+    ("fuelpump", "⛽"),
+    ("full_moon", "🌕"),
+    ("full_moon_with_face", "🌝"),
+    ("funeral_urn", "⚱\u{fe0f}"),
+    ("gabon", "🇬🇦"),
+    ("gambia", "🇬🇲"),
+    ("game_die", "🎲"),
+    ("garlic", "🧄"),
+    ("gb", "🇬🇧"),
+    ("gear", "⚙\u{fe0f}"),
+    ("gem", "💎"),
+    ("gemini", "♊"),
+    ("genie", "🧞"),
+    ("genie_man", "🧞\u{200d}♂\u{fe0f}"),
+    ("genie_woman", "🧞\u{200d}♀\u{fe0f}"),
+    ("georgia", "🇬🇪"),
+    ("ghana", "🇬🇭"),
+    ("ghost", "👻"),
+    ("gibraltar", "🇬🇮"),
+    ("gift", "🎁"),
+    ("gift_heart", "💝"),
+    ("ginger_root", "🫚"),
+    ("giraffe", "🦒"),
+    ("girl", "👧"),
+    ("globe_with_meridians", "🌐"),
+    ("gloves", "🧤"),
+    ("goal_net", "🥅"),
+    ("goat", "🐐"),
+    ("goggles", "🥽"),
+    ("golf", "⛳"),
+    ("golfing", "🏌\u{fe0f}"),
+    ("golfing_man", "🏌\u{fe0f}\u{200d}♂\u{fe0f}"),
+    ("golfing_woman", "🏌\u{fe0f}\u{200d}♀\u{fe0f}"),
+    ("goose", "🪿"),
+    ("gorilla", "🦍"),
+    ("grapes", "🍇"),
+    ("greece", "🇬🇷"),
+    ("green_apple", "🍏"),
+    ("green_book", "📗"),
+    ("green_circle", "🟢"),
+    ("green_heart", "💚"),
+    ("green_salad", "🥗"),
+    ("green_square", "🟩"),
+    ("greenland", "🇬🇱"),
+    ("grenada", "🇬🇩"),
+    ("grey_exclamation", "❕"),
+    ("grey_heart", "🩶"),
+    ("grey_question", "❔"),
+    ("grimacing", "😬"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("guadeloupe", "🇬🇵"),
+    ("guam", "🇬🇺"),
+    ("guard", "💂"),
+    ("guardsman", "💂\u{200d}♂\u{fe0f}"),
+    ("guardswoman", "💂\u{200d}♀\u{fe0f}"),
+    ("guatemala", "🇬🇹"),
+    ("guernsey", "🇬🇬"),
+    ("guide_dog", "🦮"),
+    ("guinea", "🇬🇳"),
+    ("guinea_bissau", "🇬🇼"),
+    ("guitar", "🎸"),
+    ("gun", "🔫"),
+    ("guyana", "🇬🇾"),
+    ("hair_pick", "🪮"),
+    ("haircut", "💇"),
+    ("haircut_man", "💇\u{200d}♂\u{fe0f}"),
+    ("haircut_woman", "💇\u{200d}♀\u{fe0f}"),
+    ("haiti", "🇭🇹"),
+    ("hamburger", "🍔"),
+    ("hammer", "🔨"),
+    ("hammer_and_pick", "⚒\u{fe0f}"),
+    ("hammer_and_wrench", "🛠\u{fe0f}"),
+    ("hamsa", "🪬"),
+    ("hamster", "🐹"),
+    ("hand", "✋"),
+    ("hand_over_mouth", "🤭"),
+    ("hand_with_index_finger_and_thumb_crossed", "🫰"),
+    ("handbag", "👜"),
+    ("handball_person", "🤾"),
+    ("handshake", "🤝"),
+    ("hankey", "💩"),
+    ("hash", "#\u{fe0f}\u{20e3}"),
+    ("hatched_chick", "🐥"),
+    ("hatching_chick", "🐣"),
+    ("headphones", "🎧"),
+    ("headstone", "🪦"),
+    ("health_worker", "🧑\u{200d}⚕\u{fe0f}"),
+    ("hear_no_evil", "🙉"),
+    ("heard_mcdonald_islands", "🇭🇲"),
+    ("heart", "❤\u{fe0f}"),
+    ("heart_decoration", "💟"),
+    ("heart_eyes", "😍"),
+    ("heart_eyes_cat", "😻"),
+    ("heart_hands", "🫶"),
+    ("heart_on_fire", "❤\u{fe0f}\u{200d}🔥"),
+    ("heartbeat", "💓"),
+    ("heartpulse", "💗"),
+    ("hearts", "♥\u{fe0f}"),
+    ("heavy_check_mark", "✔\u{fe0f}"),
+    ("heavy_division_sign", "➗"),
+    ("heavy_dollar_sign", "💲"),
+    ("heavy_equals_sign", "🟰"),
+    ("heavy_exclamation_mark", "❗"),
+    ("heavy_heart_exclamation", "❣\u{fe0f}"),
+    ("heavy_minus_sign", "➖"),
+    ("heavy_multiplication_x", "✖\u{fe0f}"),
+    ("heavy_plus_sign", "➕"),
+    ("hedgehog", "🦔"),
+    ("helicopter", "🚁"),
+    ("herb", "🌿"),
+    ("hibiscus", "🌺"),
+    ("high_brightness", "🔆"),
+    ("high_heel", "👠"),
+    ("hiking_boot", "🥾"),
+    ("hindu_temple", "🛕"),
+    ("hippopotamus", "🦛"),
+    ("hocho", "🔪"),
+    ("hole", "🕳\u{fe0f}"),
+    ("honduras", "🇭🇳"),
+    ("honey_pot", "🍯"),
+    ("honeybee", "🐝"),
+    ("hong_kong", "🇭🇰"),
+    ("hook", "🪝"),
+    ("horse", "🐴"),
+    ("horse_racing", "🏇"),
+    ("hospital", "🏥"),
+    ("hot_face", "🥵"),
+    ("hot_pepper", "🌶\u{fe0f}"),
+    ("hotdog", "🌭"),
+    ("hotel", "🏨"),
+    ("hotsprings", "♨\u{fe0f}"),
+    ("hourglass", "⌛"),
+    ("hourglass_flowing_sand", "⏳"),
+    ("house", "🏠"),
+    ("house_with_garden", "🏡"),
+    ("houses", "🏘\u{fe0f}"),
+    ("hugs", "🤗"),
+    ("hungary", "🇭🇺"),
+    ("hushed", "😯"),
+    ("hut", "🛖"),
+    ("hyacinth", "🪻"),
+    ("ice_cream", "🍨"),
+    ("ice_cube", "🧊"),
+    ("ice_hockey", "🏒"),
+    ("ice_skate", "⛸\u{fe0f}"),
+    ("icecream", "🍦"),
+    ("iceland", "🇮🇸"),
+    ("id", "🆔"),
+    ("identification_card", "🪪"),
+    ("ideograph_advantage", "🉐"),
+    ("imp", "👿"),
+    ("inbox_tray", "📥"),
+    ("incoming_envelope", "📨"),
+    ("index_pointing_at_the_viewer", "🫵"),
+    ("india", "🇮🇳"),
+    ("indonesia", "🇮🇩"),
+    ("infinity", "♾\u{fe0f}"),
+    ("information_desk_person", "💁"),
+    ("information_source", "ℹ\u{fe0f}"),
+    ("innocent", "😇"),
+    ("interrobang", "⁉\u{fe0f}"),
+    ("iphone", "📱"),
+    ("iran", "🇮🇷"),
+    ("iraq", "🇮🇶"),
+    ("ireland", "🇮🇪"),
+    ("isle_of_man", "🇮🇲"),
+    ("israel", "🇮🇱"),
+    ("it", "🇮🇹"),
+    ("izakaya_lantern", "🏮"),
+    ("jack_o_lantern", "🎃"),
+    ("jamaica", "🇯🇲"),
+    ("japan", "🗾"),
+    ("japanese_castle", "🏯"),
+    ("japanese_goblin", "👺"),
+    ("japanese_ogre", "👹"),
+    ("jar", "🫙"),
+    ("jeans", "👖"),
+    ("jellyfish", "🪼"),
+    ("jersey", "🇯🇪"),
+    ("jigsaw", "🧩"),
+    ("jordan", "🇯🇴"),
+    ("joy", "😂"),
+    ("joy_cat", "😹"),
+    ("joystick", "🕹\u{fe0f}"),
+    ("jp", "🇯🇵"),
+    ("judge", "🧑\u{200d}⚖\u{fe0f}"),
+    ("juggling_person", "🤹"),
+    ("kaaba", "🕋"),
+    ("kangaroo", "🦘"),
+    ("kazakhstan", "🇰🇿"),
+    ("kenya", "🇰🇪"),
+    ("key", "🔑"),
+    ("keyboard", "⌨\u{fe0f}"),
+    ("keycap_ten", "🔟"),
+    ("khanda", "🪯"),
+    ("kick_scooter", "🛴"),
+    ("kimono", "👘"),
+    ("kiribati", "🇰🇮"),
+    ("kiss", "💋"),
+    ("kissing", "😗"),
+    ("kissing_cat", "😽"),
+    ("kissing_closed_eyes", "😚"),
+    ("kissing_heart", "😘"),
+    ("kissing_smiling_eyes", "😙"),
+    ("kite", "🪁"),
+    ("kiwi_fruit", "🥝"),
+    ("kneeling_man", "🧎\u{200d}♂\u{fe0f}"),
+    ("kneeling_person", "🧎"),
+    ("kneeling_woman", "🧎\u{200d}♀\u{fe0f}"),
+    ("knife", "🔪"),
+    ("knot", "🪢"),
+    ("koala", "🐨"),
+    ("koko", "🈁"),
+    ("kosovo", "🇽🇰"),
+    ("kr", "🇰🇷"),
+    ("kuwait", "🇰🇼"),
+    ("kyrgyzstan", "🇰🇬"),
+    ("lab_coat", "🥼"),
+    ("label", "🏷\u{fe0f}"),
+    ("lacrosse", "🥍"),
+    ("ladder", "🪜"),
+    ("lady_beetle", "🐞"),
+    ("lantern", "🏮"),
+    ("laos", "🇱🇦"),
+    ("large_blue_circle", "🔵"),
+    ("large_blue_diamond", "🔷"),
+    ("large_orange_diamond", "🔶"),
+    ("last_quarter_moon", "🌗"),
+    ("last_quarter_moon_with_face", "🌜"),
+    ("latin_cross", "✝\u{fe0f}"),
+    ("latvia", "🇱🇻"),
+    ("laughing", "😆"),
+    ("leafy_green", "🥬"),
+    ("leaves", "🍃"),
+    ("lebanon", "🇱🇧"),
+    ("ledger", "📒"),
+    ("left_luggage", "🛅"),
+    ("left_right_arrow", "↔\u{fe0f}"),
+    ("left_speech_bubble", "🗨\u{fe0f}"),
+    ("leftwards_arrow_with_hook", "↩\u{fe0f}"),
+    ("leftwards_hand", "🫲"),
+    ("leftwards_pushing_hand", "🫷"),
+    ("leg", "🦵"),
+    ("lemon", "🍋"),
+    ("leo", "♌"),
+    ("leopard", "🐆"),
+    ("lesotho", "🇱🇸"),
+    ("level_slider", "🎚\u{fe0f}"),
+    ("liberia", "🇱🇷"),
+    ("libra", "♎"),
+    ("libya", "🇱🇾"),
+    ("liechtenstein", "🇱🇮"),
+    ("light_blue_heart", "🩵"),
+    ("light_rail", "🚈"),
+    ("link", "🔗"),
+    ("lion", "🦁"),
+    ("lips", "👄"),
+    ("lipstick", "💄"),
+    ("lithuania", "🇱🇹"),
+    ("lizard", "🦎"),
+    ("llama", "🦙"),
+    ("lobster", "🦞"),
+    ("lock", "🔒"),
+    ("lock_with_ink_pen", "🔏"),
+    ("lollipop", "🍭"),
+    ("long_drum", "🪘"),
+    ("loop", "➿"),
+    ("lotion_bottle", "🧴"),
+    ("lotus", "🪷"),
+    ("lotus_position", "🧘"),
+    ("lotus_position_man", "🧘\u{200d}♂\u{fe0f}"),
+    ("lotus_position_woman", "🧘\u{200d}♀\u{fe0f}"),
+    ("loud_sound", "🔊"),
+    ("loudspeaker", "📢"),
+    ("love_hotel", "🏩"),
+    ("love_letter", "💌"),
+    ("love_you_gesture", "🤟"),
+    ("low_battery", "🪫"),
+    ("low_brightness", "🔅"),
+    ("luggage", "🧳"),
+    ("lungs", "🫁"),
+    ("luxembourg", "🇱🇺"),
+    ("lying_face", "🤥"),
+    ("m", "Ⓜ\u{fe0f}"),
+    ("macau", "🇲🇴"),
+    ("macedonia", "🇲🇰"),
+    ("madagascar", "🇲🇬"),
+    ("mag", "🔍"),
+    ("mag_right", "🔎"),
+    ("mage", "🧙"),
+    ("mage_man", "🧙\u{200d}♂\u{fe0f}"),
+    ("mage_woman", "🧙\u{200d}♀\u{fe0f}"),
+    ("magic_wand", "🪄"),
+    ("magnet", "🧲"),
+    ("mahjong", "🀄"),
+    ("mailbox", "📫"),
+    ("mailbox_closed", "📪"),
+    ("mailbox_with_mail", "📬"),
+    ("mailbox_with_no_mail", "📭"),
+    ("malawi", "🇲🇼"),
+    ("malaysia", "🇲🇾"),
+    ("maldives", "🇲🇻"),
+    ("male_detective", "🕵\u{fe0f}\u{200d}♂\u{fe0f}"),
+    ("male_sign", "♂\u{fe0f}"),
+    ("mali", "🇲🇱"),
+    ("malta", "🇲🇹"),
+    ("mammoth", "🦣"),
+    ("man", "👨"),
+    ("man_artist", "👨\u{200d}🎨"),
+    ("man_astronaut", "👨\u{200d}🚀"),
+    ("man_beard", "🧔\u{200d}♂\u{fe0f}"),
+    ("man_cartwheeling", "🤸\u{200d}♂\u{fe0f}"),
+    ("man_cook", "👨\u{200d}🍳"),
+    ("man_dancing", "🕺"),
+    ("man_facepalming", "🤦\u{200d}♂\u{fe0f}"),
+    ("man_factory_worker", "👨\u{200d}🏭"),
+    ("man_farmer", "👨\u{200d}🌾"),
+    ("man_feeding_baby", "👨\u{200d}🍼"),
+    ("man_firefighter", "👨\u{200d}🚒"),
+    ("man_health_worker", "👨\u{200d}⚕\u{fe0f}"),
+    ("man_in_manual_wheelchair", "👨\u{200d}🦽"),
+    ("man_in_motorized_wheelchair", "👨\u{200d}🦼"),
+    ("man_in_tuxedo", "🤵\u{200d}♂\u{fe0f}"),
+    ("man_judge", "👨\u{200d}⚖\u{fe0f}"),
+    ("man_juggling", "🤹\u{200d}♂\u{fe0f}"),
+    ("man_mechanic", "👨\u{200d}🔧"),
+    ("man_office_worker", "👨\u{200d}💼"),
+    ("man_pilot", "👨\u{200d}✈\u{fe0f}"),
+    ("man_playing_handball", "🤾\u{200d}♂\u{fe0f}"),
+    ("man_playing_water_polo", "🤽\u{200d}♂\u{fe0f}"),
+    ("man_scientist", "👨\u{200d}🔬"),
+    ("man_shrugging", "🤷\u{200d}♂\u{fe0f}"),
+    ("man_singer", "👨\u{200d}🎤"),
+    ("man_student", "👨\u{200d}🎓"),
+    ("man_teacher", "👨\u{200d}🏫"),
+    ("man_technologist", "👨\u{200d}💻"),
+    ("man_with_gua_pi_mao", "👲"),
+    ("man_with_probing_cane", "👨\u{200d}🦯"),
+    ("man_with_turban", "👳\u{200d}♂\u{fe0f}"),
+    ("man_with_veil", "👰\u{200d}♂\u{fe0f}"),
+    ("mandarin", "🍊"),
+    ("mango", "🥭"),
+    ("mans_shoe", "👞"),
+    ("mantelpiece_clock", "🕰\u{fe0f}"),
+    ("manual_wheelchair", "🦽"),
+    ("maple_leaf", "🍁"),
+    ("maracas", "🪇"),
+    ("marshall_islands", "🇲🇭"),
+    ("martial_arts_uniform", "🥋"),
+    ("martinique", "🇲🇶"),
+    ("mask", "😷"),
+    ("massage", "💆"),
+    ("massage_man", "💆\u{200d}♂\u{fe0f}"),
+    ("massage_woman", "💆\u{200d}♀\u{fe0f}"),
+    ("mate", "🧉"),
+    ("mauritania", "🇲🇷"),
+    ("mauritius", "🇲🇺"),
+    ("mayotte", "🇾🇹"),
+    ("meat_on_bone", "🍖"),
+    ("mechanic", "🧑\u{200d}🔧"),
+    ("mechanical_arm", "🦾"),
+    ("mechanical_leg", "🦿"),
+    ("medal_military", "🎖\u{fe0f}"),
+    ("medal_sports", "🏅"),
+    ("medical_symbol", "⚕\u{fe0f}"),
+    ("mega", "📣"),
+    ("melon", "🍈"),
+    ("melting_face", "🫠"),
+    ("memo", "📝"),
+    ("men_wrestling", "🤼\u{200d}♂\u{fe0f}"),
+    ("mending_heart", "❤\u{fe0f}\u{200d}🩹"),
+    ("menorah", "🕎"),
+    ("mens", "🚹"),
+    ("mermaid", "🧜\u{200d}♀\u{fe0f}"),
+    ("merman", "🧜\u{200d}♂\u{fe0f}"),
+    ("merperson", "🧜"),
+    ("metal", "🤘"),
+    ("metro", "🚇"),
+    ("mexico", "🇲🇽"),
+    ("microbe", "🦠"),
+    ("micronesia", "🇫🇲"),
+    ("microphone", "🎤"),
+    ("microscope", "🔬"),
+    ("middle_finger", "🖕"),
+    ("military_helmet", "🪖"),
+    ("milk_glass", "🥛"),
+    ("milky_way", "🌌"),
+    ("minibus", "🚐"),
+    ("minidisc", "💽"),
+    ("mirror", "🪞"),
+    ("mirror_ball", "🪩"),
+    ("mobile_phone_off", "📴"),
+    ("moldova", "🇲🇩"),
+    ("monaco", "🇲🇨"),
+    ("money_mouth_face", "🤑"),
+    ("money_with_wings", "💸"),
+    ("moneybag", "💰"),
+    ("mongolia", "🇲🇳"),
+    ("monkey", "🐒"),
+    ("monkey_face", "🐵"),
+    ("monocle_face", "🧐"),
+    ("monorail", "🚝"),
+    ("montenegro", "🇲🇪"),
+    ("montserrat", "🇲🇸"),
+    ("moon", "🌔"),
+    ("moon_cake", "🥮"),
+    ("moose", "🫎"),
+    ("morocco", "🇲🇦"),
+    ("mortar_board", "🎓"),
+    ("mosque", "🕌"),
+    ("mosquito", "🦟"),
+    ("motor_boat", "🛥\u{fe0f}"),
+    ("motor_scooter", "🛵"),
+    ("motorcycle", "🏍\u{fe0f}"),
+    ("motorized_wheelchair", "🦼"),
+    ("motorway", "🛣\u{fe0f}"),
+    ("mount_fuji", "🗻"),
+    ("mountain", "⛰\u{fe0f}"),
+    ("mountain_bicyclist", "🚵"),
+    ("mountain_biking_man", "🚵\u{200d}♂\u{fe0f}"),
+    ("mountain_biking_woman", "🚵\u{200d}♀\u{fe0f}"),
+    ("mountain_cableway", "🚠"),
+    ("mountain_railway", "🚞"),
+    ("mountain_snow", "🏔\u{fe0f}"),
+    ("mouse", "🐭"),
+    ("mouse2", "🐁"),
+    ("mouse_trap", "🪤"),
+    ("movie_camera", "🎥"),
+    ("moyai", "🗿"),
+    ("mozambique", "🇲🇿"),
+    ("mrs_claus", "🤶"),
+    ("muscle", "💪"),
+    ("mushroom", "🍄"),
+    ("musical_keyboard", "🎹"),
+    ("musical_note", "🎵"),
+    ("musical_score", "🎼"),
+    ("mute", "🔇"),
+    ("mx_claus", "🧑\u{200d}🎄"),
+    ("myanmar", "🇲🇲"),
+    ("nail_care", "💅"),
+    ("name_badge", "📛"),
+    ("namibia", "🇳🇦"),
+    ("national_park", "🏞\u{fe0f}"),
+    ("nauru", "🇳🇷"),
+    ("nauseated_face", "🤢"),
+    ("nazar_amulet", "🧿"),
+    ("necktie", "👔"),
+    ("negative_squared_cross_mark", "❎"),
+    ("nepal", "🇳🇵"),
+    ("nerd_face", "🤓"),
+    ("nest_with_eggs", "🪺"),
+    ("nesting_dolls", "🪆"),
+    ("netherlands", "🇳🇱"),
+    ("neutral_face", "😐"),
+    ("new", "🆕"),
+    ("new_caledonia", "🇳🇨"),
+    ("new_moon", "🌑"),
+    ("new_moon_with_face", "🌚"),
+    ("new_zealand", "🇳🇿"),
+    ("newspaper", "📰"),
+    ("newspaper_roll", "🗞\u{fe0f}"),
+    ("next_track_button", "⏭\u{fe0f}"),
+    ("ng", "🆖"),
+    ("ng_man", "🙅\u{200d}♂\u{fe0f}"),
+    ("ng_woman", "🙅\u{200d}♀\u{fe0f}"),
+    ("nicaragua", "🇳🇮"),
+    ("niger", "🇳🇪"),
+    ("nigeria", "🇳🇬"),
+    ("night_with_stars", "🌃"),
+    ("nine", "9\u{fe0f}\u{20e3}"),
+    ("ninja", "🥷"),
+    ("niue", "🇳🇺"),
+    ("no_bell", "🔕"),
+    ("no_bicycles", "🚳"),
+    ("no_entry", "⛔"),
+    ("no_entry_sign", "🚫"),
+    ("no_good", "🙅"),
+    ("no_good_man", "🙅\u{200d}♂\u{fe0f}"),
+    ("no_good_woman", "🙅\u{200d}♀\u{fe0f}"),
+    ("no_mobile_phones", "📵"),
+    ("no_mouth", "😶"),
+    ("no_pedestrians", "🚷"),
+    ("no_smoking", "🚭"),
+    ("non-potable_water", "🚱"),
+    ("norfolk_island", "🇳🇫"),
+    ("north_korea", "🇰🇵"),
+    ("northern_mariana_islands", "🇲🇵"),
+    ("norway", "🇳🇴"),
+    ("nose", "👃"),
+    ("notebook", "📓"),
+    ("notebook_with_decorative_cover", "📔"),
+    ("notes", "🎶"),
+    ("nut_and_bolt", "🔩"),
+    ("o", "⭕"),
+    ("o2", "🅾\u{fe0f}"),
+    ("ocean", "🌊"),
+    ("octopus", "🐙"),
+    ("oden", "🍢"),
+    ("office", "🏢"),
+    ("office_worker", "🧑\u{200d}💼"),
+    ("oil_drum", "🛢\u{fe0f}"),
+    ("ok", "🆗"),
+    ("ok_hand", "👌"),
+    ("ok_man", "🙆\u{200d}♂\u{fe0f}"),
+    ("ok_person", "🙆"),
+    ("ok_woman", "🙆\u{200d}♀\u{fe0f}"),
+    ("old_key", "🗝\u{fe0f}"),
+    ("older_adult", "🧓"),
+    ("older_man", "👴"),
+    ("older_woman", "👵"),
+    ("olive", "🫒"),
+    ("om", "🕉\u{fe0f}"),
+    ("oman", "🇴🇲"),
+    ("on", "🔛"),
+    ("oncoming_automobile", "🚘"),
+    ("oncoming_bus", "🚍"),
+    ("oncoming_police_car", "🚔"),
+    ("oncoming_taxi", "🚖"),
+    ("one", "1\u{fe0f}\u{20e3}"),
+    ("one_piece_swimsuit", "🩱"),
+    ("onion", "🧅"),
+    ("open_book", "📖"),
+    ("open_file_folder", "📂"),
+    ("open_hands", "👐"),
+    ("open_mouth", "😮"),
+    ("open_umbrella", "☂\u{fe0f}"),
+    ("ophiuchus", "⛎"),
+    ("orange", "🍊"),
+    ("orange_book", "📙"),
+    ("orange_circle", "🟠"),
+    ("orange_heart", "🧡"),
+    ("orange_square", "🟧"),
+    ("orangutan", "🦧"),
+    ("orthodox_cross", "☦\u{fe0f}"),
+    ("otter", "🦦"),
+    ("outbox_tray", "📤"),
+    ("owl", "🦉"),
+    ("ox", "🐂"),
+    ("oyster", "🦪"),
+    ("package", "📦"),
+    ("page_facing_up", "📄"),
+    ("page_with_curl", "📃"),
+    ("pager", "📟"),
+    ("paintbrush", "🖌\u{fe0f}"),
+    ("pakistan", "🇵🇰"),
+    ("palau", "🇵🇼"),
+    ("palestinian_territories", "🇵🇸"),
+    ("palm_down_hand", "🫳"),
+    ("palm_tree", "🌴"),
+    ("palm_up_hand", "🫴"),
+    ("palms_up_together", "🤲"),
+    ("panama", "🇵🇦"),
+    ("pancakes", "🥞"),
+    ("panda_face", "🐼"),
+    ("paperclip", "📎"),
+    ("paperclips", "🖇\u{fe0f}"),
+    ("papua_new_guinea", "🇵🇬"),
+    ("parachute", "🪂"),
+    ("paraguay", "🇵🇾"),
+    ("parasol_on_ground", "⛱\u{fe0f}"),
+    ("parking", "🅿\u{fe0f}"),
+    ("parrot", "🦜"),
+    ("part_alternation_mark", "〽\u{fe0f}"),
+    ("partly_sunny", "⛅"),
+    ("partying_face", "🥳"),
+    ("passenger_ship", "🛳\u{fe0f}"),
+    ("passport_control", "🛂"),
+    ("pause_button", "⏸\u{fe0f}"),
+    ("paw_prints", "🐾"),
+    ("pea_pod", "🫛"),
+    ("peace_symbol", "☮\u{fe0f}"),
+    ("peach", "🍑"),
+    ("peacock", "🦚"),
+    ("peanuts", "🥜"),
+    ("pear", "🍐"),
+    ("pen", "🖊\u{fe0f}"),
+    ("pencil", "📝"),
+    ("pencil2", "✏\u{fe0f}"),
+    ("penguin", "🐧"),
+    ("pensive", "😔"),
+    ("people_holding_hands", "🧑\u{200d}🤝\u{200d}🧑"),
+    ("people_hugging", "🫂"),
+    ("performing_arts", "🎭"),
+    ("persevere", "😣"),
+    ("person_bald", "🧑\u{200d}🦲"),
+    ("person_curly_hair", "🧑\u{200d}🦱"),
+    ("person_feeding_baby", "🧑\u{200d}🍼"),
+    ("person_fencing", "🤺"),
+    ("person_in_manual_wheelchair", "🧑\u{200d}🦽"),
+    ("person_in_motorized_wheelchair", "🧑\u{200d}🦼"),
+    ("person_in_tuxedo", "🤵"),
+    ("person_red_hair", "🧑\u{200d}🦰"),
+    ("person_white_hair", "🧑\u{200d}🦳"),
+    ("person_with_crown", "🫅"),
+    ("person_with_probing_cane", "🧑\u{200d}🦯"),
+    ("person_with_turban", "👳"),
+    ("person_with_veil", "👰"),
+    ("peru", "🇵🇪"),
+    ("petri_dish", "🧫"),
+    ("philippines", "🇵🇭"),
+    ("phone", "☎\u{fe0f}"),
+    ("pick", "⛏\u{fe0f}"),
+    ("pickup_truck", "🛻"),
+    ("pie", "🥧"),
+    ("pig", "🐷"),
+    ("pig2", "🐖"),
+    ("pig_nose", "🐽"),
+    ("pill", "💊"),
+    ("pilot", "🧑\u{200d}✈\u{fe0f}"),
+    ("pinata", "🪅"),
+    ("pinched_fingers", "🤌"),
+    ("pinching_hand", "🤏"),
+    ("pineapple", "🍍"),
+    ("ping_pong", "🏓"),
+    ("pink_heart", "🩷"),
+    ("pirate_flag", "🏴\u{200d}☠\u{fe0f}"),
+    ("pisces", "♓"),
+    ("pitcairn_islands", "🇵🇳"),
+    ("pizza", "🍕"),
+    ("placard", "🪧"),
+    ("place_of_worship", "🛐"),
+    ("plate_with_cutlery", "🍽\u{fe0f}"),
+    ("play_or_pause_button", "⏯\u{fe0f}"),
+    ("playground_slide", "🛝"),
+    ("pleading_face", "🥺"),
+    ("plunger", "🪠"),
+    ("point_down", "👇"),
+    ("point_left", "👈"),
+    ("point_right", "👉"),
+    ("point_up", "☝\u{fe0f}"),
+    ("point_up_2", "👆"),
+    ("poland", "🇵🇱"),
+    ("polar_bear", "🐻\u{200d}❄\u{fe0f}"),
+    ("police_car", "🚓"),
+    ("police_officer", "👮"),
+    ("policeman", "👮\u{200d}♂\u{fe0f}"),
+    ("policewoman", "👮\u{200d}♀\u{fe0f}"),
+    ("poodle", "🐩"),
+    ("poop", "💩"),
+    ("popcorn", "🍿"),
+    ("portugal", "🇵🇹"),
+    ("post_office", "🏣"),
+    ("postal_horn", "📯"),
+    ("postbox", "📮"),
+    ("potable_water", "🚰"),
+    ("potato", "🥔"),
+    ("potted_plant", "🪴"),
+    ("pouch", "👝"),
+    ("poultry_leg", "🍗"),
+    ("pound", "💷"),
+    ("pouring_liquid", "🫗"),
+    ("pout", "😡"),
+    ("pouting_cat", "😾"),
+    ("pouting_face", "🙎"),
+    ("pouting_man", "🙎\u{200d}♂\u{fe0f}"),
+    ("pouting_woman", "🙎\u{200d}♀\u{fe0f}"),
+    ("pray", "🙏"),
+    ("prayer_beads", "📿"),
+    ("pregnant_man", "🫃"),
+    ("pregnant_person", "🫄"),
+    ("pregnant_woman", "🤰"),
+    ("pretzel", "🥨"),
+    ("previous_track_button", "⏮\u{fe0f}"),
+    ("prince", "🤴"),
+    ("princess", "👸"),
+    ("printer", "🖨\u{fe0f}"),
+    ("probing_cane", "🦯"),
+    ("puerto_rico", "🇵🇷"),
+    ("punch", "👊"),
+    ("purple_circle", "🟣"),
+    ("purple_heart", "💜"),
+    ("purple_square", "🟪"),
+    ("purse", "👛"),
+    ("pushpin", "📌"),
+    ("put_litter_in_its_place", "🚮"),
+    ("qatar", "🇶🇦"),
+    ("question", "❓"),
+    ("rabbit", "🐰"),
+    ("rabbit2", "🐇"),
+    ("raccoon", "🦝"),
+    ("racehorse", "🐎"),
+    ("racing_car", "🏎\u{fe0f}"),
+    ("radio", "📻"),
+    ("radio_button", "🔘"),
+    ("radioactive", "☢\u{fe0f}"),
+    ("rage", "😡"),
+    ("railway_car", "🚃"),
+    ("railway_track", "🛤\u{fe0f}"),
+    ("rainbow", "🌈"),
+    ("rainbow_flag", "🏳\u{fe0f}\u{200d}🌈"),
+    ("raised_back_of_hand", "🤚"),
+    ("raised_eyebrow", "🤨"),
+    ("raised_hand", "✋"),
+    ("raised_hand_with_fingers_splayed", "🖐\u{fe0f}"),
+    ("raised_hands", "🙌"),
+    ("raising_hand", "🙋"),
+    ("raising_hand_man", "🙋\u{200d}♂\u{fe0f}"),
+    ("raising_hand_woman", "🙋\u{200d}♀\u{fe0f}"),
+    ("ram", "🐏"),
+    ("ramen", "🍜"),
+    ("rat", "🐀"),
+    ("razor", "🪒"),
+    ("receipt", "🧾"),
+    ("record_button", "⏺\u{fe0f}"),
+    ("recycle", "♻\u{fe0f}"),
+    ("red_car", "🚗"),
+    ("red_circle", "🔴"),
+    ("red_envelope", "🧧"),
+    ("red_haired_man", "👨\u{200d}🦰"),
+    ("red_haired_woman", "👩\u{200d}🦰"),
+    ("red_square", "🟥"),
+    ("registered", "®\u{fe0f}"),
+    ("relaxed", "☺\u{fe0f}"),
+    ("relieved", "😌"),
+    ("reminder_ribbon", "🎗\u{fe0f}"),
+    ("repeat", "🔁"),
+    ("repeat_one", "🔂"),
+    ("rescue_worker_helmet", "⛑\u{fe0f}"),
+    ("restroom", "🚻"),
+    ("reunion", "🇷🇪"),
+    ("revolving_hearts", "💞"),
+    ("rewind", "⏪"),
+    ("rhinoceros", "🦏"),
+    ("ribbon", "🎀"),
+    ("rice", "🍚"),
+    ("rice_ball", "🍙"),
+    ("rice_cracker", "🍘"),
+    ("rice_scene", "🎑"),
+    ("right_anger_bubble", "🗯\u{fe0f}"),
+    ("rightwards_hand", "🫱"),
+    ("rightwards_pushing_hand", "🫸"),
+    ("ring", "💍"),
+    ("ring_buoy", "🛟"),
+    ("ringed_planet", "🪐"),
+    ("robot", "🤖"),
+    ("rock", "🪨"),
+    ("rocket", "🚀"),
+    ("rofl", "🤣"),
+    ("roll_eyes", "🙄"),
+    ("roll_of_paper", "🧻"),
+    ("roller_coaster", "🎢"),
+    ("roller_skate", "🛼"),
+    ("romania", "🇷🇴"),
+    ("rooster", "🐓"),
+    ("rose", "🌹"),
+    ("rosette", "🏵\u{fe0f}"),
+    ("rotating_light", "🚨"),
+    ("round_pushpin", "📍"),
+    ("rowboat", "🚣"),
+    ("rowing_man", "🚣\u{200d}♂\u{fe0f}"),
+    ("rowing_woman", "🚣\u{200d}♀\u{fe0f}"),
+    ("ru", "🇷🇺"),
+    ("rugby_football", "🏉"),
+    ("runner", "🏃"),
+    ("running", "🏃"),
+    ("running_man", "🏃\u{200d}♂\u{fe0f}"),
+    ("running_shirt_with_sash", "🎽"),
+    ("running_woman", "🏃\u{200d}♀\u{fe0f}"),
+    ("rwanda", "🇷🇼"),
+    ("sa", "🈂\u{fe0f}"),
+    ("safety_pin", "🧷"),
+    ("safety_vest", "🦺"),
+    ("sagittarius", "♐"),
+    ("sailboat", "⛵"),
+    ("sake", "🍶"),
+    ("salt", "🧂"),
+    ("saluting_face", "🫡"),
+    ("samoa", "🇼🇸"),
+    ("san_marino", "🇸🇲"),
+    ("sandal", "👡"),
+    ("sandwich", "🥪"),
+    ("santa", "🎅"),
+    ("sao_tome_principe", "🇸🇹"),
+    ("sari", "🥻"),
+    ("sassy_man", "💁\u{200d}♂\u{fe0f}"),
+    ("sassy_woman", "💁\u{200d}♀\u{fe0f}"),
+    ("satellite", "📡"),
+    ("satisfied", "😆"),
+    ("saudi_arabia", "🇸🇦"),
+    ("sauna_man", "🧖\u{200d}♂\u{fe0f}"),
+    ("sauna_person", "🧖"),
+    ("sauna_woman", "🧖\u{200d}♀\u{fe0f}"),
+    ("sauropod", "🦕"),
+    ("saxophone", "🎷"),
+    ("scarf", "🧣"),
+    ("school", "🏫"),
+    ("school_satchel", "🎒"),
+    ("scientist", "🧑\u{200d}🔬"),
+    ("scissors", "✂\u{fe0f}"),
+    ("scorpion", "🦂"),
+    ("scorpius", "♏"),
+    (
+        "scotland",
+        "🏴\u{e0067}\u{e0062}\u{e0073}\u{e0063}\u{e0074}\u{e007f}",
+    ),
+    ("scream", "😱"),
+    ("scream_cat", "🙀"),
+    ("screwdriver", "🪛"),
+    ("scroll", "📜"),
+    ("seal", "🦭"),
+    ("seat", "💺"),
+    ("secret", "㊙\u{fe0f}"),
+    ("see_no_evil", "🙈"),
+    ("seedling", "🌱"),
+    ("selfie", "🤳"),
+    ("senegal", "🇸🇳"),
+    ("serbia", "🇷🇸"),
+    ("service_dog", "🐕\u{200d}🦺"),
+    ("seven", "7\u{fe0f}\u{20e3}"),
+    ("sewing_needle", "🪡"),
+    ("seychelles", "🇸🇨"),
+    ("shaking_face", "🫨"),
+    ("shallow_pan_of_food", "🥘"),
+    ("shamrock", "☘\u{fe0f}"),
+    ("shark", "🦈"),
+    ("shaved_ice", "🍧"),
+    ("sheep", "🐑"),
+    ("shell", "🐚"),
+    ("shield", "🛡\u{fe0f}"),
+    ("shinto_shrine", "⛩\u{fe0f}"),
+    ("ship", "🚢"),
+    ("shirt", "👕"),
+    ("shit", "💩"),
+    ("shoe", "👞"),
+    ("shopping", "🛍\u{fe0f}"),
+    ("shopping_cart", "🛒"),
+    ("shorts", "🩳"),
+    ("shower", "🚿"),
+    ("shrimp", "🦐"),
+    ("shrug", "🤷"),
+    ("shushing_face", "🤫"),
+    ("sierra_leone", "🇸🇱"),
+    ("signal_strength", "📶"),
+    ("singapore", "🇸🇬"),
+    ("singer", "🧑\u{200d}🎤"),
+    ("sint_maarten", "🇸🇽"),
+    ("six", "6\u{fe0f}\u{20e3}"),
+    ("six_pointed_star", "🔯"),
+    ("skateboard", "🛹"),
+    ("ski", "🎿"),
+    ("skier", "⛷\u{fe0f}"),
+    ("skull", "💀"),
+    ("skull_and_crossbones", "☠\u{fe0f}"),
+    ("skunk", "🦨"),
+    ("sled", "🛷"),
+    ("sleeping", "😴"),
+    ("sleeping_bed", "🛌"),
+    ("sleepy", "😪"),
+    ("slightly_frowning_face", "🙁"),
+    ("slightly_smiling_face", "🙂"),
+    ("slot_machine", "🎰"),
+    ("sloth", "🦥"),
+    ("slovakia", "🇸🇰"),
+    ("slovenia", "🇸🇮"),
+    ("small_airplane", "🛩\u{fe0f}"),
+    ("small_blue_diamond", "🔹"),
+    ("small_orange_diamond", "🔸"),
+    ("small_red_triangle", "🔺"),
+    ("small_red_triangle_down", "🔻"),
+    ("smile", "😄"),
+    ("smile_cat", "😸"),
+    ("smiley", "😃"),
+    ("smiley_cat", "😺"),
+    ("smiling_face_with_tear", "🥲"),
+    ("smiling_face_with_three_hearts", "🥰"),
+    ("smiling_imp", "😈"),
+    ("smirk", "😏"),
+    ("smirk_cat", "😼"),
+    ("smoking", "🚬"),
+    ("snail", "🐌"),
+    ("snake", "🐍"),
+    ("sneezing_face", "🤧"),
+    ("snowboarder", "🏂"),
+    ("snowflake", "❄\u{fe0f}"),
+    ("snowman", "⛄"),
+    ("snowman_with_snow", "☃\u{fe0f}"),
+    ("soap", "🧼"),
+    ("sob", "😭"),
+    ("soccer", "⚽"),
+    ("socks", "🧦"),
+    ("softball", "🥎"),
+    ("solomon_islands", "🇸🇧"),
+    ("somalia", "🇸🇴"),
+    ("soon", "🔜"),
+    ("sos", "🆘"),
+    ("sound", "🔉"),
+    ("south_africa", "🇿🇦"),
+    ("south_georgia_south_sandwich_islands", "🇬🇸"),
+    ("south_sudan", "🇸🇸"),
+    ("space_invader", "👾"),
+    ("spades", "♠\u{fe0f}"),
+    ("spaghetti", "🍝"),
+    ("sparkle", "❇\u{fe0f}"),
+    ("sparkler", "🎇"),
+    ("sparkles", "✨"),
+    ("sparkling_heart", "💖"),
+    ("speak_no_evil", "🙊"),
+    ("speaker", "🔈"),
+    ("speaking_head", "🗣\u{fe0f}"),
+    ("speech_balloon", "💬"),
+    ("speedboat", "🚤"),
+    ("spider", "🕷\u{fe0f}"),
+    ("spider_web", "🕸\u{fe0f}"),
+    ("spiral_calendar", "🗓\u{fe0f}"),
+    ("spiral_notepad", "🗒\u{fe0f}"),
+    ("sponge", "🧽"),
+    ("spoon", "🥄"),
+    ("squid", "🦑"),
+    ("sri_lanka", "🇱🇰"),
+    ("st_barthelemy", "🇧🇱"),
+    ("st_helena", "🇸🇭"),
+    ("st_kitts_nevis", "🇰🇳"),
+    ("st_lucia", "🇱🇨"),
+    ("st_martin", "🇲🇫"),
+    ("st_pierre_miquelon", "🇵🇲"),
+    ("st_vincent_grenadines", "🇻🇨"),
+    ("stadium", "🏟\u{fe0f}"),
+    ("standing_man", "🧍\u{200d}♂\u{fe0f}"),
+    ("standing_person", "🧍"),
+    ("standing_woman", "🧍\u{200d}♀\u{fe0f}"),
+    ("star", "⭐"),
+    ("star2", "🌟"),
+    ("star_and_crescent", "☪\u{fe0f}"),
+    ("star_of_david", "✡\u{fe0f}"),
+    ("star_struck", "🤩"),
+    ("stars", "🌠"),
+    ("station", "🚉"),
+    ("statue_of_liberty", "🗽"),
+    ("steam_locomotive", "🚂"),
+    ("stethoscope", "🩺"),
+    ("stew", "🍲"),
+    ("stop_button", "⏹\u{fe0f}"),
+    ("stop_sign", "🛑"),
+    ("stopwatch", "⏱\u{fe0f}"),
+    ("straight_ruler", "📏"),
+    ("strawberry", "🍓"),
+    ("stuck_out_tongue", "😛"),
+    ("stuck_out_tongue_closed_eyes", "😝"),
+    ("stuck_out_tongue_winking_eye", "😜"),
+    ("student", "🧑\u{200d}🎓"),
+    ("studio_microphone", "🎙\u{fe0f}"),
+    ("stuffed_flatbread", "🥙"),
+    ("sudan", "🇸🇩"),
+    ("sun_behind_large_cloud", "🌥\u{fe0f}"),
+    ("sun_behind_rain_cloud", "🌦\u{fe0f}"),
+    ("sun_behind_small_cloud", "🌤\u{fe0f}"),
+    ("sun_with_face", "🌞"),
+    ("sunflower", "🌻"),
+    ("sunglasses", "😎"),
+    ("sunny", "☀\u{fe0f}"),
+    ("sunrise", "🌅"),
+    ("sunrise_over_mountains", "🌄"),
+    ("superhero", "🦸"),
+    ("superhero_man", "🦸\u{200d}♂\u{fe0f}"),
+    ("superhero_woman", "🦸\u{200d}♀\u{fe0f}"),
+    ("supervillain", "🦹"),
+    ("supervillain_man", "🦹\u{200d}♂\u{fe0f}"),
+    ("supervillain_woman", "🦹\u{200d}♀\u{fe0f}"),
+    ("surfer", "🏄"),
+    ("surfing_man", "🏄\u{200d}♂\u{fe0f}"),
+    ("surfing_woman", "🏄\u{200d}♀\u{fe0f}"),
+    ("suriname", "🇸🇷"),
+    ("sushi", "🍣"),
+    ("suspension_railway", "🚟"),
+    ("svalbard_jan_mayen", "🇸🇯"),
+    ("swan", "🦢"),
+    ("swaziland", "🇸🇿"),
+    ("sweat", "😓"),
+    ("sweat_drops", "💦"),
+    ("sweat_smile", "😅"),
+    ("sweden", "🇸🇪"),
+    ("sweet_potato", "🍠"),
+    ("swim_brief", "🩲"),
+    ("swimmer", "🏊"),
+    ("swimming_man", "🏊\u{200d}♂\u{fe0f}"),
+    ("swimming_woman", "🏊\u{200d}♀\u{fe0f}"),
+    ("switzerland", "🇨🇭"),
+    ("symbols", "🔣"),
+    ("synagogue", "🕍"),
+    ("syria", "🇸🇾"),
+    ("syringe", "💉"),
+    ("t-rex", "🦖"),
+    ("taco", "🌮"),
+    ("tada", "🎉"),
+    ("taiwan", "🇹🇼"),
+    ("tajikistan", "🇹🇯"),
+    ("takeout_box", "🥡"),
+    ("tamale", "🫔"),
+    ("tanabata_tree", "🎋"),
+    ("tangerine", "🍊"),
+    ("tanzania", "🇹🇿"),
+    ("taurus", "♉"),
+    ("taxi", "🚕"),
+    ("tea", "🍵"),
+    ("teacher", "🧑\u{200d}🏫"),
+    ("teapot", "🫖"),
+    ("technologist", "🧑\u{200d}💻"),
+    ("teddy_bear", "🧸"),
+    ("telephone", "☎\u{fe0f}"),
+    ("telephone_receiver", "📞"),
+    ("telescope", "🔭"),
+    ("tennis", "🎾"),
+    ("tent", "⛺"),
+    ("test_tube", "🧪"),
+    ("thailand", "🇹🇭"),
+    ("thermometer", "🌡\u{fe0f}"),
+    ("thinking", "🤔"),
+    ("thong_sandal", "🩴"),
+    ("thought_balloon", "💭"),
+    ("thread", "🧵"),
+    ("three", "3\u{fe0f}\u{20e3}"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("ticket", "🎫"),
+    ("tickets", "🎟\u{fe0f}"),
+    ("tiger", "🐯"),
+    ("tiger2", "🐅"),
+    ("timer_clock", "⏲\u{fe0f}"),
+    ("timor_leste", "🇹🇱"),
+    ("tipping_hand_man", "💁\u{200d}♂\u{fe0f}"),
+    ("tipping_hand_person", "💁"),
+    ("tipping_hand_woman", "💁\u{200d}♀\u{fe0f}"),
+    ("tired_face", "😫"),
+    ("tm", "™\u{fe0f}"),
+    ("togo", "🇹🇬"),
+    ("toilet", "🚽"),
+    ("tokelau", "🇹🇰"),
+    ("tokyo_tower", "🗼"),
+    ("tomato", "🍅"),
+    ("tonga", "🇹🇴"),
+    ("tongue", "👅"),
+    ("toolbox", "🧰"),
+    ("tooth", "🦷"),
+    ("toothbrush", "🪥"),
+    ("top", "🔝"),
+    ("tophat", "🎩"),
+    ("tornado", "🌪\u{fe0f}"),
+    ("tr", "🇹🇷"),
+    ("trackball", "🖲\u{fe0f}"),
+    ("tractor", "🚜"),
+    ("traffic_light", "🚥"),
+    ("train", "🚋"),
+    ("train2", "🚆"),
+    ("tram", "🚊"),
+    ("transgender_flag", "🏳\u{fe0f}\u{200d}⚧\u{fe0f}"),
+    ("transgender_symbol", "⚧\u{fe0f}"),
+    ("triangular_flag_on_post", "🚩"),
+    ("triangular_ruler", "📐"),
+    ("trident", "🔱"),
+    ("trinidad_tobago", "🇹🇹"),
+    ("tristan_da_cunha", "🇹🇦"),
+    ("triumph", "😤"),
+    ("troll", "🧌"),
+    ("trolleybus", "🚎"),
+    ("trophy", "🏆"),
+    ("tropical_drink", "🍹"),
+    ("tropical_fish", "🐠"),
+    ("truck", "🚚"),
+    ("trumpet", "🎺"),
+    ("tshirt", "👕"),
+    ("tulip", "🌷"),
+    ("tumbler_glass", "🥃"),
+    ("tunisia", "🇹🇳"),
+    ("turkey", "🦃"),
+    ("turkmenistan", "🇹🇲"),
+    ("turks_caicos_islands", "🇹🇨"),
+    ("turtle", "🐢"),
+    ("tuvalu", "🇹🇻"),
+    ("tv", "📺"),
+    ("twisted_rightwards_arrows", "🔀"),
+    ("two", "2\u{fe0f}\u{20e3}"),
+    ("two_hearts", "💕"),
+    ("two_men_holding_hands", "👬"),
+    ("two_women_holding_hands", "👭"),
+    ("u5272", "🈹"),
+    ("u5408", "🈴"),
+    ("u55b6", "🈺"),
+    ("u6307", "🈯"),
+    ("u6708", "🈷\u{fe0f}"),
+    ("u6709", "🈶"),
+    ("u6e80", "🈵"),
+    ("u7121", "🈚"),
+    ("u7533", "🈸"),
+    ("u7981", "🈲"),
+    ("u7a7a", "🈳"),
+    ("uganda", "🇺🇬"),
+    ("uk", "🇬🇧"),
+    ("ukraine", "🇺🇦"),
+    ("umbrella", "☔"),
+    ("unamused", "😒"),
+    ("underage", "🔞"),
+    ("unicorn", "🦄"),
+    ("united_arab_emirates", "🇦🇪"),
+    ("united_nations", "🇺🇳"),
+    ("unlock", "🔓"),
+    ("up", "🆙"),
+    ("upside_down_face", "🙃"),
+    ("uruguay", "🇺🇾"),
+    ("us", "🇺🇸"),
+    ("us_outlying_islands", "🇺🇲"),
+    ("us_virgin_islands", "🇻🇮"),
+    ("uzbekistan", "🇺🇿"),
+    ("v", "✌\u{fe0f}"),
+    ("vampire", "🧛"),
+    ("vampire_man", "🧛\u{200d}♂\u{fe0f}"),
+    ("vampire_woman", "🧛\u{200d}♀\u{fe0f}"),
+    ("vanuatu", "🇻🇺"),
+    ("vatican_city", "🇻🇦"),
+    ("venezuela", "🇻🇪"),
+    ("vertical_traffic_light", "🚦"),
+    ("vhs", "📼"),
+    ("vibration_mode", "📳"),
+    ("video_camera", "📹"),
+    ("video_game", "🎮"),
+    ("vietnam", "🇻🇳"),
+    ("violin", "🎻"),
+    ("virgo", "♍"),
+    ("volcano", "🌋"),
+    ("volleyball", "🏐"),
+    ("vomiting_face", "🤮"),
+    ("vs", "🆚"),
+    ("vulcan_salute", "🖖"),
+    ("waffle", "🧇"),
+    (
+        "wales",
+        "🏴\u{e0067}\u{e0062}\u{e0077}\u{e006c}\u{e0073}\u{e007f}",
+    ),
+    ("walking", "🚶"),
+    ("walking_man", "🚶\u{200d}♂\u{fe0f}"),
+    ("walking_woman", "🚶\u{200d}♀\u{fe0f}"),
+    ("wallis_futuna", "🇼🇫"),
+    ("waning_crescent_moon", "🌘"),
+    ("waning_gibbous_moon", "🌖"),
+    ("warning", "⚠\u{fe0f}"),
+    ("wastebasket", "🗑\u{fe0f}"),
+    ("watch", "⌚"),
+    ("water_buffalo", "🐃"),
+    ("water_polo", "🤽"),
+    ("watermelon", "🍉"),
+    ("wave", "👋"),
+    ("wavy_dash", "〰\u{fe0f}"),
+    ("waxing_crescent_moon", "🌒"),
+    ("waxing_gibbous_moon", "🌔"),
+    ("wc", "🚾"),
+    ("weary", "😩"),
+    ("wedding", "💒"),
+    ("weight_lifting", "🏋\u{fe0f}"),
+    ("weight_lifting_man", "🏋\u{fe0f}\u{200d}♂\u{fe0f}"),
+    ("weight_lifting_woman", "🏋\u{fe0f}\u{200d}♀\u{fe0f}"),
+    ("western_sahara", "🇪🇭"),
+    ("whale", "🐳"),
+    ("whale2", "🐋"),
+    ("wheel", "🛞"),
+    ("wheel_of_dharma", "☸\u{fe0f}"),
+    ("wheelchair", "♿"),
+    ("white_check_mark", "✅"),
+    ("white_circle", "⚪"),
+    ("white_flag", "🏳\u{fe0f}"),
+    ("white_flower", "💮"),
+    ("white_haired_man", "👨\u{200d}🦳"),
+    ("white_haired_woman", "👩\u{200d}🦳"),
+    ("white_heart", "🤍"),
+    ("white_large_square", "⬜"),
+    ("white_medium_small_square", "◽"),
+    ("white_medium_square", "◻\u{fe0f}"),
+    ("white_small_square", "▫\u{fe0f}"),
+    ("white_square_button", "🔳"),
+    ("wilted_flower", "🥀"),
+    ("wind_chime", "🎐"),
+    ("wind_face", "🌬\u{fe0f}"),
+    ("window", "🪟"),
+    ("wine_glass", "🍷"),
+    ("wing", "🪽"),
+    ("wink", "😉"),
+    ("wireless", "🛜"),
+    ("wolf", "🐺"),
+    ("woman", "👩"),
+    ("woman_artist", "👩\u{200d}🎨"),
+    ("woman_astronaut", "👩\u{200d}🚀"),
+    ("woman_beard", "🧔\u{200d}♀\u{fe0f}"),
+    ("woman_cartwheeling", "🤸\u{200d}♀\u{fe0f}"),
+    ("woman_cook", "👩\u{200d}🍳"),
+    ("woman_dancing", "💃"),
+    ("woman_facepalming", "🤦\u{200d}♀\u{fe0f}"),
+    ("woman_factory_worker", "👩\u{200d}🏭"),
+    ("woman_farmer", "👩\u{200d}🌾"),
+    ("woman_feeding_baby", "👩\u{200d}🍼"),
+    ("woman_firefighter", "👩\u{200d}🚒"),
+    ("woman_health_worker", "👩\u{200d}⚕\u{fe0f}"),
+    ("woman_in_manual_wheelchair", "👩\u{200d}🦽"),
+    ("woman_in_motorized_wheelchair", "👩\u{200d}🦼"),
+    ("woman_in_tuxedo", "🤵\u{200d}♀\u{fe0f}"),
+    ("woman_judge", "👩\u{200d}⚖\u{fe0f}"),
+    ("woman_juggling", "🤹\u{200d}♀\u{fe0f}"),
+    ("woman_mechanic", "👩\u{200d}🔧"),
+    ("woman_office_worker", "👩\u{200d}💼"),
+    ("woman_pilot", "👩\u{200d}✈\u{fe0f}"),
+    ("woman_playing_handball", "🤾\u{200d}♀\u{fe0f}"),
+    ("woman_playing_water_polo", "🤽\u{200d}♀\u{fe0f}"),
+    ("woman_scientist", "👩\u{200d}🔬"),
+    ("woman_shrugging", "🤷\u{200d}♀\u{fe0f}"),
+    ("woman_singer", "👩\u{200d}🎤"),
+    ("woman_student", "👩\u{200d}🎓"),
+    ("woman_teacher", "👩\u{200d}🏫"),
+    ("woman_technologist", "👩\u{200d}💻"),
+    ("woman_with_headscarf", "🧕"),
+    ("woman_with_probing_cane", "👩\u{200d}🦯"),
+    ("woman_with_turban", "👳\u{200d}♀\u{fe0f}"),
+    ("woman_with_veil", "👰\u{200d}♀\u{fe0f}"),
+    ("womans_clothes", "👚"),
+    ("womans_hat", "👒"),
+    ("women_wrestling", "🤼\u{200d}♀\u{fe0f}"),
+    ("womens", "🚺"),
+    ("wood", "🪵"),
+    ("woozy_face", "🥴"),
+    ("world_map", "🗺\u{fe0f}"),
+    ("worm", "🪱"),
+    ("worried", "😟"),
+    ("wrench", "🔧"),
+    ("wrestling", "🤼"),
+    ("writing_hand", "✍\u{fe0f}"),
+    ("x", "❌"),
+    ("x_ray", "🩻"),
+    ("yarn", "🧶"),
+    ("yawning_face", "🥱"),
+    ("yellow_circle", "🟡"),
+    ("yellow_heart", "💛"),
+    ("yellow_square", "🟨"),
+    ("yemen", "🇾🇪"),
+    ("yen", "💴"),
+    ("yin_yang", "☯\u{fe0f}"),
+    ("yo_yo", "🪀"),
+    ("yum", "😋"),
+    ("zambia", "🇿🇲"),
+    ("zany_face", "🤪"),
+    ("zap", "⚡"),
+    ("zebra", "🦓"),
+    ("zero", "0\u{fe0f}\u{20e3}"),
+    ("zimbabwe", "🇿🇼"),
+    ("zipper_mouth_face", "🤐"),
+    ("zombie", "🧟"),
+    ("zombie_man", "🧟\u{200d}♂\u{fe0f}"),
+    ("zombie_woman", "🧟\u{200d}♀\u{fe0f}"),
+    ("zzz", "💤"),
+];
+
+/// Ranges of characters which are displayed as emoji by default.
+const EMOJI_PRESENTATION: &[(char, char)] = &[
+    ('\u{231A}', '\u{231B}'),
+    ('\u{23E9}', '\u{23EC}'),
+    ('\u{23F0}', '\u{23F0}'),
+    ('\u{23F3}', '\u{23F3}'),
+    ('\u{25FD}', '\u{25FE}'),
+    ('\u{2614}', '\u{2615}'),
+    ('\u{2648}', '\u{2653}'),
+    ('\u{267F}', '\u{267F}'),
+    ('\u{2693}', '\u{2693}'),
+    ('\u{26A1}', '\u{26A1}'),
+    ('\u{26AA}', '\u{26AB}'),
+    ('\u{26BD}', '\u{26BE}'),
+    ('\u{26C4}', '\u{26C5}'),
+    ('\u{26CE}', '\u{26CE}'),
+    ('\u{26D4}', '\u{26D4}'),
+    ('\u{26EA}', '\u{26EA}'),
+    ('\u{26F2}', '\u{26F3}'),
+    ('\u{26F5}', '\u{26F5}'),
+    ('\u{26FA}', '\u{26FA}'),
+    ('\u{26FD}', '\u{26FD}'),
+    ('\u{2705}', '\u{2705}'),
+    ('\u{270A}', '\u{270B}'),
+    ('\u{2728}', '\u{2728}'),
+    ('\u{274C}', '\u{274C}'),
+    ('\u{274E}', '\u{274E}'),
+    ('\u{2753}', '\u{2755}'),
+    ('\u{2757}', '\u{2757}'),
+    ('\u{2795}', '\u{2797}'),
+    ('\u{27B0}', '\u{27B0}'),
+    ('\u{27BF}', '\u{27BF}'),
+    ('\u{2B1B}', '\u{2B1C}'),
+    ('\u{2B50}', '\u{2B50}'),
+    ('\u{2B55}', '\u{2B55}'),
+    ('\u{1F004}', '\u{1F004}'),
+    ('\u{1F0CF}', '\u{1F0CF}'),
+    ('\u{1F18E}', '\u{1F18E}'),
+    ('\u{1F191}', '\u{1F19A}'),
+    ('\u{1F201}', '\u{1F201}'),
+    ('\u{1F21A}', '\u{1F21A}'),
+    ('\u{1F22F}', '\u{1F22F}'),
+    ('\u{1F232}', '\u{1F236}'),
+    ('\u{1F238}', '\u{1F23A}'),
+    ('\u{1F250}', '\u{1F251}'),
+    ('\u{1F300}', '\u{1F320}'),
+    ('\u{1F32D}', '\u{1F335}'),
+    ('\u{1F337}', '\u{1F37C}'),
+    ('\u{1F37E}', '\u{1F393}'),
+    ('\u{1F3A0}', '\u{1F3CA}'),
+    ('\u{1F3CF}', '\u{1F3D3}'),
+    ('\u{1F3E0}', '\u{1F3F0}'),
+    ('\u{1F3F4}', '\u{1F3F4}'),
+    ('\u{1F3F8}', '\u{1F3FA}'),
+    ('\u{1F400}', '\u{1F43E}'),
+    ('\u{1F440}', '\u{1F440}'),
+    ('\u{1F442}', '\u{1F4FC}'),
+    ('\u{1F4FF}', '\u{1F53D}'),
+    ('\u{1F54B}', '\u{1F54E}'),
+    ('\u{1F550}', '\u{1F567}'),
+    ('\u{1F57A}', '\u{1F57A}'),
+    ('\u{1F595}', '\u{1F596}'),
+    ('\u{1F5A4}', '\u{1F5A4}'),
+    ('\u{1F5FB}', '\u{1F64F}'),
+    ('\u{1F680}', '\u{1F6C5}'),
+    ('\u{1F6CC}', '\u{1F6CC}'),
+    ('\u{1F6D0}', '\u{1F6D2}'),
+    ('\u{1F6D5}', '\u{1F6D7}'),
+    ('\u{1F6DC}', '\u{1F6DF}'),
+    ('\u{1F6EB}', '\u{1F6EC}'),
+    ('\u{1F6F4}', '\u{1F6FC}'),
+    ('\u{1F7E0}', '\u{1F7EB}'),
+    ('\u{1F7F0}', '\u{1F7F0}'),
+    ('\u{1F90C}', '\u{1F93A}'),
+    ('\u{1F93C}', '\u{1F945}'),
+    ('\u{1F947}', '\u{1F9AF}'),
+    ('\u{1F9B4}', '\u{1F9FF}'),
+    ('\u{1FA70}', '\u{1FA7C}'),
+    ('\u{1FA80}', '\u{1FA89}'),
+    ('\u{1FA8F}', '\u{1FAC6}'),
+    ('\u{1FACE}', '\u{1FADC}'),
+    ('\u{1FADF}', '\u{1FAE9}'),
+    ('\u{1FAF0}', '\u{1FAF8}'),
+];
+
+/// Ranges of characters which are displayed as text by default, but which are emoji when followed
+/// by an emoji variation selector.
+const TEXT_PRESENTATION: &[(char, char)] = &[
+    ('\u{A9}', '\u{A9}'),
+    ('\u{AE}', '\u{AE}'),
+    ('\u{203C}', '\u{203C}'),
+    ('\u{2049}', '\u{2049}'),
+    ('\u{2122}', '\u{2122}'),
+    ('\u{2139}', '\u{2139}'),
+    ('\u{2194}', '\u{2199}'),
+    ('\u{21A9}', '\u{21AA}'),
+    ('\u{2328}', '\u{2328}'),
+    ('\u{23CF}', '\u{23CF}'),
+    ('\u{23ED}', '\u{23EF}'),
+    ('\u{23F1}', '\u{23F2}'),
+    ('\u{23F8}', '\u{23FA}'),
+    ('\u{24C2}', '\u{24C2}'),
+    ('\u{25AA}', '\u{25AB}'),
+    ('\u{25B6}', '\u{25B6}'),
+    ('\u{25C0}', '\u{25C0}'),
+    ('\u{25FB}', '\u{25FC}'),
+    ('\u{2600}', '\u{2604}'),
+    ('\u{260E}', '\u{260E}'),
+    ('\u{2611}', '\u{2611}'),
+    ('\u{2618}', '\u{2618}'),
+    ('\u{261D}', '\u{261D}'),
+    ('\u{2620}', '\u{2620}'),
+    ('\u{2622}', '\u{2623}'),
+    ('\u{2626}', '\u{2626}'),
+    ('\u{262A}', '\u{262A}'),
+    ('\u{262E}', '\u{262F}'),
+    ('\u{2638}', '\u{263A}'),
+    ('\u{2640}', '\u{2640}'),
+    ('\u{2642}', '\u{2642}'),
+    ('\u{265F}', '\u{2660}'),
+    ('\u{2663}', '\u{2663}'),
+    ('\u{2665}', '\u{2666}'),
+    ('\u{2668}', '\u{2668}'),
+    ('\u{267B}', '\u{267B}'),
+    ('\u{267E}', '\u{267E}'),
+    ('\u{2692}', '\u{2692}'),
+    ('\u{2694}', '\u{2697}'),
+    ('\u{2699}', '\u{2699}'),
+    ('\u{269B}', '\u{269C}'),
+    ('\u{26A0}', '\u{26A0}'),
+    ('\u{26A7}', '\u{26A7}'),
+    ('\u{26B0}', '\u{26B1}'),
+    ('\u{26C8}', '\u{26C8}'),
+    ('\u{26CF}', '\u{26CF}'),
+    ('\u{26D1}', '\u{26D1}'),
+    ('\u{26D3}', '\u{26D3}'),
+    ('\u{26E9}', '\u{26E9}'),
+    ('\u{26F0}', '\u{26F1}'),
+    ('\u{26F4}', '\u{26F4}'),
+    ('\u{26F7}', '\u{26F9}'),
+    ('\u{2702}', '\u{2702}'),
+    ('\u{2708}', '\u{2709}'),
+    ('\u{270C}', '\u{270D}'),
+    ('\u{270F}', '\u{270F}'),
+    ('\u{2712}', '\u{2712}'),
+    ('\u{2714}', '\u{2714}'),
+    ('\u{2716}', '\u{2716}'),
+    ('\u{271D}', '\u{271D}'),
+    ('\u{2721}', '\u{2721}'),
+    ('\u{2733}', '\u{2734}'),
+    ('\u{2744}', '\u{2744}'),
+    ('\u{2747}', '\u{2747}'),
+    ('\u{2763}', '\u{2764}'),
+    ('\u{27A1}', '\u{27A1}'),
+    ('\u{2934}', '\u{2935}'),
+    ('\u{2B05}', '\u{2B07}'),
+    ('\u{3030}', '\u{3030}'),
+    ('\u{303D}', '\u{303D}'),
+    ('\u{3297}', '\u{3297}'),
+    ('\u{3299}', '\u{3299}'),
+    ('\u{1F170}', '\u{1F171}'),
+    ('\u{1F17E}', '\u{1F17F}'),
+    ('\u{1F202}', '\u{1F202}'),
+    ('\u{1F237}', '\u{1F237}'),
+    ('\u{1F321}', '\u{1F321}'),
+    ('\u{1F324}', '\u{1F32C}'),
+    ('\u{1F336}', '\u{1F336}'),
+    ('\u{1F37D}', '\u{1F37D}'),
+    ('\u{1F396}', '\u{1F397}'),
+    ('\u{1F399}', '\u{1F39B}'),
+    ('\u{1F39E}', '\u{1F39F}'),
+    ('\u{1F3CB}', '\u{1F3CE}'),
+    ('\u{1F3D4}', '\u{1F3DF}'),
+    ('\u{1F3F3}', '\u{1F3F3}'),
+    ('\u{1F3F5}', '\u{1F3F5}'),
+    ('\u{1F3F7}', '\u{1F3F7}'),
+    ('\u{1F43F}', '\u{1F43F}'),
+    ('\u{1F441}', '\u{1F441}'),
+    ('\u{1F4FD}', '\u{1F4FD}'),
+    ('\u{1F549}', '\u{1F54A}'),
+    ('\u{1F56F}', '\u{1F570}'),
+    ('\u{1F573}', '\u{1F579}'),
+    ('\u{1F587}', '\u{1F587}'),
+    ('\u{1F58A}', '\u{1F58D}'),
+    ('\u{1F590}', '\u{1F590}'),
+    ('\u{1F5A5}', '\u{1F5A5}'),
+    ('\u{1F5A8}', '\u{1F5A8}'),
+    ('\u{1F5B1}', '\u{1F5B2}'),
+    ('\u{1F5BC}', '\u{1F5BC}'),
+    ('\u{1F5C2}', '\u{1F5C4}'),
+    ('\u{1F5D1}', '\u{1F5D3}'),
+    ('\u{1F5DC}', '\u{1F5DE}'),
+    ('\u{1F5E1}', '\u{1F5E1}'),
+    ('\u{1F5E3}', '\u{1F5E3}'),
+    ('\u{1F5E8}', '\u{1F5E8}'),
+    ('\u{1F5EF}', '\u{1F5EF}'),
+    ('\u{1F5F3}', '\u{1F5F3}'),
+    ('\u{1F5FA}', '\u{1F5FA}'),
+    ('\u{1F6CB}', '\u{1F6CB}'),
+    ('\u{1F6CD}', '\u{1F6CF}'),
+    ('\u{1F6E0}', '\u{1F6E5}'),
+    ('\u{1F6E9}', '\u{1F6E9}'),
+    ('\u{1F6F0}', '\u{1F6F0}'),
+    ('\u{1F6F3}', '\u{1F6F3}'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcodes_are_sorted() {
+        assert!(SHORTCODES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn shortcodes() {
+        assert_eq!(Some("🚀"), get_by_shortcode("rocket"));
+        assert_eq!(Some("👍"), get_by_shortcode("+1"));
+        assert_eq!(Some("👍"), get_by_shortcode("thumbsup"));
+        assert_eq!(Some("©\u{fe0f}"), get_by_shortcode("copyright"));
+        assert_eq!(None, get_by_shortcode("nope"));
+        assert_eq!(None, get_by_shortcode(""));
+    }
+
+    #[test]
+    fn presentation() {
+        assert!(is_emoji_presentation('🚀'));
+        assert!(!is_emoji_presentation('©'));
+        assert!(!is_emoji_presentation('a'));
+        assert!(is_text_presentation('©'));
+        assert!(!is_text_presentation('🚀'));
+        assert!(!is_text_presentation('é'));
+    }
+}
@@ -131,10 +131,10 @@ impl<'a> Default for Formatter<'a> {
 }
 
 impl<'a> Format for Formatter<'a> {
-    fn fmt<W, I>(&self, out: &mut W, mut headers: I) -> io::Result<()>
+    fn fmt<'h, W, I>(&self, out: &mut W, mut headers: I) -> io::Result<()>
     where
         W: Write,
-        I: Iterator<Item = Header>,
+        I: Iterator<Item = Header<'h>>,
     {
         use Formatter::*;
 
@@ -178,10 +178,10 @@ impl<'a> Format for Formatter<'a> {
 ///
 /// // Implement the `Format` trait. In this case, output the members of each `Header`.
 /// impl Format for DebugFormatter {
-///     fn fmt<W, I>(&self, writer: &mut W, mut headers: I) -> io::Result<()>
+///     fn fmt<'h, W, I>(&self, writer: &mut W, mut headers: I) -> io::Result<()>
 ///     where
 ///         W: Write,
-///         I: Iterator<Item = Header>,
+///         I: Iterator<Item = Header<'h>>,
 ///     {
 ///         headers.try_for_each(|h| {
 ///             writeln!(
@@ -217,20 +217,20 @@ pub trait Format: Default {
     /// the operation could not be completed.
     ///
     /// [`Header`]: struct.Header.html
-    fn fmt<W, I>(&self, writer: &mut W, headers: I) -> io::Result<()>
+    fn fmt<'h, W, I>(&self, writer: &mut W, headers: I) -> io::Result<()>
     where
         W: Write,
-        I: Iterator<Item = Header>;
+        I: Iterator<Item = Header<'h>>;
 }
 
-fn format_alternating_bullets<W: Write>(out: &mut W, header: Header) -> io::Result<()> {
+fn format_alternating_bullets<W: Write>(out: &mut W, header: Header<'_>) -> io::Result<()> {
     let level = header.level();
     let len = ALTERNATING_BULLET_STYLES.len();
 
     format_symbols(out, header, ALTERNATING_BULLET_STYLES[(level - 1) % len])
 }
 
-fn format_symbols<W: Write>(out: &mut W, header: Header, bullet: &str) -> io::Result<()> {
+fn format_symbols<W: Write>(out: &mut W, header: Header<'_>, bullet: &str) -> io::Result<()> {
     let level = header.level();
     let indent = bullet.chars().count() + 1;
 
@@ -247,7 +247,8 @@ fn format_symbols<W: Write>(out: &mut W, header: Header, bullet: &str) -> io::Re
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::headers;
+    use crate::{headers, inline};
+    use pulldown_cmark::{Event, Parser, Tag};
     use std::str;
 
     const MD: &str =
@@ -361,4 +362,53 @@ mod tests {
         assert_eq!(Some("     wat. [Conclusion](#conclusion)"), lines.next());
         assert_eq!(None, lines.next());
     }
+
+    /// Parses a formatted table of contents, returning the rendered text and destination of each
+    /// link.
+    fn parse_links(toc: &str) -> Vec<(String, String)> {
+        let mut links = Vec::new();
+        let mut current: Option<(String, String)> = None;
+
+        for event in Parser::new(toc) {
+            match event {
+                Event::Start(Tag::Link(_, url, _)) => {
+                    current = Some((String::new(), url.to_string()));
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((ref mut content, _)) = current {
+                        content.push_str(&text);
+                    }
+                }
+                Event::End(Tag::Link(_, _, _)) => links.extend(current.take()),
+                _ => (),
+            }
+        }
+        links
+    }
+
+    // A pipe in a code span is escaped as well, which only round trips through a GFM table cell
+    #[test]
+    fn escaped_titles_round_trip() {
+        let md = r"# Array[T] access
+# Use \\ for paths
+# Path C:\
+# A ` tick
+# Pipe | char
+# Code `a[0]` span
+# Escaped \[1\] and ``a ` b``
+# See [the *spec*](https://spec.example)
+";
+        let headers = headers(md).collect::<Vec<_>>();
+        let mut out = Vec::new();
+        Formatter::DashBullets
+            .fmt(&mut out, headers.clone().into_iter())
+            .unwrap();
+        let links = parse_links(str::from_utf8(&out).unwrap());
+
+        assert_eq!(headers.len(), links.len());
+        for (header, (text, url)) in headers.iter().zip(links) {
+            assert_eq!(inline::text_content(header.inlines()), text);
+            assert_eq!(header.anchor(), url);
+        }
+    }
 }
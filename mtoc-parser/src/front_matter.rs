@@ -0,0 +1,407 @@
+// Copyright 2019 Fletcher Nichol and/or applicable contributors.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license (see <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
+// distributed except according to those terms.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Returns the [`FrontMatter`] block at the start of a Markdown string slice, if any.
+///
+/// Front matter is a block of metadata used by static site generators such as Jekyll and Hugo. A
+/// YAML front matter block is delimited by lines containing only `---` (the closing line may also
+/// be `...`) and a TOML front matter block is delimited by lines containing only `+++`. The
+/// opening delimiter must be the first line of the source.
+///
+/// A Markdown parser would otherwise read a YAML front matter block as a thematic break followed
+/// by a setext heading, so the [`headers`] function and the [`WriterBuilder`] skip over any front
+/// matter.
+///
+/// # Examples
+///
+/// ```rust
+/// use mtoc_parser::front_matter;
+///
+/// let markdown = "---\ntitle: Getting Started\nlayout: page\n---\n\n## Install\n";
+/// let matter = front_matter(markdown).unwrap();
+///
+/// assert_eq!(Some("Getting Started"), matter.title().as_ref().map(AsRef::as_ref));
+/// assert_eq!("\n## Install\n", &markdown[matter.range().end..]);
+///
+/// assert!(front_matter("# Title\n").is_none());
+/// ```
+///
+/// [`FrontMatter`]: struct.FrontMatter.html
+/// [`WriterBuilder`]: struct.WriterBuilder.html
+/// [`headers`]: fn.headers.html
+pub fn front_matter(buf: &str) -> Option<FrontMatter<'_>> {
+    let mut lines = Lines::new(buf);
+    let (language, closing) = match lines.next()?.1.trim_end() {
+        "---" => (Language::Yaml, &["---", "..."][..]),
+        "+++" => (Language::Toml, &["+++"][..]),
+        _ => return None,
+    };
+    let content_start = lines.offset;
+
+    while let Some((start, line)) = lines.next() {
+        if closing.contains(&line.trim_end()) {
+            return Some(FrontMatter {
+                language,
+                content: &buf[content_start..start],
+                range: 0..lines.offset,
+            });
+        }
+    }
+    None
+}
+
+/// A block of YAML or TOML metadata at the start of a Markdown document.
+///
+/// `FrontMatter` is produced by the [`front_matter`] function. See its documentation for more.
+///
+/// [`front_matter`]: fn.front_matter.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrontMatter<'a> {
+    language: Language,
+    content: &'a str,
+    range: Range<usize>,
+}
+
+/// The language of a front matter block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Language {
+    Yaml,
+    Toml,
+}
+
+impl<'a> FrontMatter<'a> {
+    /// Returns the byte range of the front matter block in the source string slice, including its
+    /// delimiting lines.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the content of the front matter block, excluding its delimiting lines.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use mtoc_parser::front_matter;
+    ///
+    /// let front_matter = front_matter("+++\ntitle = \"Usage\"\n+++\n").unwrap();
+    ///
+    /// assert_eq!("title = \"Usage\"\n", front_matter.content());
+    /// ```
+    pub fn content(&self) -> &'a str {
+        self.content
+    }
+
+    /// Returns the value of the top-level `title` field, if any.
+    ///
+    /// Only a title given on a single line as a plain or quoted string (for YAML) or as a basic or
+    /// literal string (for TOML) is recognized. Quoted strings are unescaped, so a title which
+    /// needs no unescaping borrows from the source string slice.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use mtoc_parser::front_matter;
+    ///
+    /// let yaml = front_matter("---\ntitle: \"Tips & \\\"Tricks\\\"\"\n---\n").unwrap();
+    /// let toml = front_matter("+++\ntitle = 'C:\\Users'\n+++\n").unwrap();
+    ///
+    /// assert_eq!(Some("Tips & \"Tricks\""), yaml.title().as_ref().map(AsRef::as_ref));
+    /// assert_eq!(Some("C:\\Users"), toml.title().as_ref().map(AsRef::as_ref));
+    /// ```
+    pub fn title(&self) -> Option<Cow<'a, str>> {
+        let title = match self.language {
+            Language::Yaml => yaml_title(self.content),
+            Language::Toml => toml_title(self.content),
+        }?;
+
+        if title.is_empty() {
+            None
+        } else {
+            Some(title)
+        }
+    }
+}
+
+/// Returns the value of a top-level `title` key in YAML content.
+fn yaml_title(content: &str) -> Option<Cow<'_, str>> {
+    content.lines().find_map(|line| {
+        let value = after(after(line, "title")?.trim_start(), ":")?;
+        let value = value.trim();
+
+        if let Some(quoted) = after(value, "\"") {
+            unescape(quoted)
+        } else if let Some(quoted) = after(value, "'") {
+            // A single-quoted YAML string escapes a quote by doubling it
+            let end = single_quoted_end(quoted)?;
+            let quoted = &quoted[..end];
+            Some(if quoted.contains("''") {
+                Cow::Owned(quoted.replace("''", "'"))
+            } else {
+                Cow::Borrowed(quoted)
+            })
+        } else {
+            // A plain scalar ends at a comment
+            let value = match value.find(" #") {
+                Some(idx) => value[..idx].trim_end(),
+                None => value,
+            };
+            Some(Cow::Borrowed(value))
+        }
+    })
+}
+
+/// Returns the value of a top-level `title` key in TOML content, that is, one which appears before
+/// any table header.
+fn toml_title(content: &str) -> Option<Cow<'_, str>> {
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            return None;
+        }
+        let value = match after(line, "title") {
+            Some(rest) => match after(rest.trim_start(), "=") {
+                Some(value) => value.trim_start(),
+                None => continue,
+            },
+            None => continue,
+        };
+
+        return if let Some(quoted) = after(value, "\"") {
+            unescape(quoted)
+        } else if let Some(quoted) = after(value, "'") {
+            // A literal TOML string has no escapes
+            quoted.find('\'').map(|end| Cow::Borrowed(&quoted[..end]))
+        } else {
+            None
+        };
+    }
+    None
+}
+
+/// Returns the text after the given prefix, or `None` if the text does not start with it.
+fn after<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    if text.starts_with(prefix) {
+        Some(&text[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Returns the end of a single-quoted YAML string, skipping any doubled quotes.
+fn single_quoted_end(quoted: &str) -> Option<usize> {
+    let bytes = quoted.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            if bytes.get(i + 1) == Some(&b'\'') {
+                i += 2;
+                continue;
+            }
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Unescapes a double-quoted string up to its closing quote, returning `None` if it is not closed.
+///
+/// Only the common escapes shared by YAML and TOML are supported: `\"`, `\\`, `\t`, `\n` and `\r`.
+/// Any other escaped character is kept as it is.
+fn unescape(quoted: &str) -> Option<Cow<'_, str>> {
+    let mut unescaped = String::new();
+    let mut chars = quoted.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next()?.1 {
+                't' => unescaped.push('\t'),
+                'n' => unescaped.push('\n'),
+                'r' => unescaped.push('\r'),
+                escaped => unescaped.push(escaped),
+            },
+            '"' => {
+                return Some(if unescaped.len() == idx {
+                    Cow::Borrowed(&quoted[..idx])
+                } else {
+                    Cow::Owned(unescaped)
+                });
+            }
+            c => unescaped.push(c),
+        }
+    }
+    None
+}
+
+/// An iterator of the lines of a string slice along with their starting offsets, which keeps
+/// track of the offset just past the last line returned (including its line ending).
+struct Lines<'a> {
+    buf: &'a str,
+    offset: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn new(buf: &'a str) -> Self {
+        Lines { buf, offset: 0 }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.buf.len() {
+            return None;
+        }
+
+        let start = self.offset;
+        let rest = &self.buf[start..];
+        let (line, len) = match rest.find('\n') {
+            Some(idx) => (&rest[..idx], idx + 1),
+            None => (rest, rest.len()),
+        };
+        self.offset += len;
+
+        let line = if line.ends_with('\r') {
+            &line[..line.len() - 1]
+        } else {
+            line
+        };
+
+        Some((start, line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn title(md: &str) -> Option<String> {
+        front_matter(md)
+            .and_then(|front_matter| front_matter.title())
+            .map(Cow::into_owned)
+    }
+
+    #[test]
+    fn yaml() {
+        let md = "---\ntitle: Usage\n---\n# Body\n";
+        let front_matter = front_matter(md).unwrap();
+
+        assert_eq!(0..21, front_matter.range());
+        assert_eq!("title: Usage\n", front_matter.content());
+        assert_eq!(Some("Usage".to_string()), title(md));
+    }
+
+    #[test]
+    fn yaml_closed_with_dots() {
+        let md = "---\ntitle: Usage\n...\n";
+
+        assert_eq!(Some(0..21), front_matter(md).map(|f| f.range()));
+    }
+
+    #[test]
+    fn toml() {
+        let md = "+++\ntitle = \"Usage\"\n+++\n# Body\n";
+        let front_matter = front_matter(md).unwrap();
+
+        assert_eq!(0..24, front_matter.range());
+        assert_eq!("title = \"Usage\"\n", front_matter.content());
+        assert_eq!(Some("Usage".to_string()), title(md));
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let md = "---\r\ntitle: Usage\r\n---\r\n# Body\r\n";
+
+        assert_eq!(Some(0..24), front_matter(md).map(|f| f.range()));
+        assert_eq!(Some("Usage".to_string()), title(md));
+    }
+
+    #[test]
+    fn empty() {
+        let md = "---\n---\n";
+
+        assert_eq!(Some(0..8), front_matter(md).map(|f| f.range()));
+        assert_eq!(None, title(md));
+    }
+
+    #[test]
+    fn mismatched_delimiters() {
+        assert_eq!(None, front_matter("---\ntitle = \"Usage\"\n+++\n"));
+        assert_eq!(None, front_matter("+++\ntitle: Usage\n---\n"));
+    }
+
+    #[test]
+    fn unclosed() {
+        assert_eq!(None, front_matter("---\ntitle: Usage\n"));
+    }
+
+    #[test]
+    fn not_at_start() {
+        assert_eq!(None, front_matter("\n---\ntitle: Usage\n---\n"));
+        assert_eq!(None, front_matter("# Title\n---\ntitle: Usage\n---\n"));
+    }
+
+    #[test]
+    fn thematic_break_is_not_front_matter() {
+        assert_eq!(None, front_matter("----\ntitle: Usage\n----\n"));
+        assert_eq!(None, front_matter("- - -\ntitle: Usage\n- - -\n"));
+    }
+
+    #[test]
+    fn yaml_titles() {
+        let yaml = |value: &str| title(&format!("---\nlayout: page\ntitle: {}\n---\n", value));
+
+        assert_eq!(Some("Plain".to_string()), yaml("Plain"));
+        assert_eq!(Some("Plain".to_string()), yaml("Plain # comment"));
+        assert_eq!(Some("C# Tips".to_string()), yaml("C# Tips"));
+        assert_eq!(Some("Double".to_string()), yaml("\"Double\""));
+        assert_eq!(Some("A \"B\"".to_string()), yaml(r#""A \"B\"""#));
+        assert_eq!(Some("Single".to_string()), yaml("'Single'"));
+        assert_eq!(Some("It's".to_string()), yaml("'It''s'"));
+        assert_eq!(None, yaml("\"Unclosed"));
+        assert_eq!(None, yaml(""));
+    }
+
+    #[test]
+    fn yaml_nested_title_is_ignored() {
+        let md = "---\nseo:\n  title: Nested\n---\n";
+
+        assert_eq!(None, title(md));
+    }
+
+    #[test]
+    fn toml_titles() {
+        let toml =
+            |value: &str| title(&format!("+++\ndate = 2019-01-01\ntitle = {}\n+++\n", value));
+
+        assert_eq!(Some("Basic".to_string()), toml("\"Basic\""));
+        assert_eq!(Some("A \"B\"".to_string()), toml(r#""A \"B\"""#));
+        assert_eq!(Some("C:\\Users".to_string()), toml("'C:\\Users'"));
+        assert_eq!(None, toml("Bare"));
+    }
+
+    #[test]
+    fn toml_table_title_is_ignored() {
+        let md = "+++\n[params]\ntitle = \"Nested\"\n+++\n";
+
+        assert_eq!(None, title(md));
+    }
+
+    #[test]
+    fn titles_borrow_when_unescaped() {
+        let md = "---\ntitle: \"Usage\"\n---\n";
+
+        match front_matter(md).unwrap().title() {
+            Some(Cow::Borrowed(_)) => (),
+            title => panic!("title should be borrowed: {:?}", title),
+        }
+    }
+}
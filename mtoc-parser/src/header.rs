@@ -5,26 +5,83 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
 // distributed except according to those terms.
 
-use crate::normalize;
-use pulldown_cmark::{Event, OffsetIter, Parser, Tag};
-use std::collections::HashSet;
+use crate::front_matter::front_matter;
+use crate::inline::{self, Inline, InlineCollector};
+use crate::normalize::{self, Emoji, Normalization};
+use crate::options::ParserOptions;
+use crate::slug::{AnchorSlugger, Slugger};
+use pulldown_cmark::{Event, LinkType, OffsetIter, Tag};
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
 use std::convert::TryInto;
+use std::error;
 use std::fmt;
 use std::ops::Range;
 use std::usize;
 
+lazy_static::lazy_static! {
+    // Regular expression representing an HTML comment which omits a heading (or, with `subtree`,
+    // a heading and all of its sub-headings) from the table of contents
+    static ref OMIT_RE: Regex =
+        Regex::new(r"(?i)^<!--\s*omit\s+(subtree\s+)?(?:in|from)\s+toc\s*-->$").unwrap();
+
+    // Regular expression representing the opening tag of a raw HTML heading element
+    static ref HTML_HEADING_START_RE: Regex = Regex::new(r"(?i)<h[1-6][\s>]").unwrap();
+
+    // Regular expression representing a raw HTML heading element at the start of the given text,
+    // capturing its level, its attributes, its content and the level of its closing tag
+    static ref HTML_HEADING_RE: Regex =
+        Regex::new(r"(?is)^<h([1-6])(\s[^>]*)?>(.*?)</h([1-6])\s*>").unwrap();
+
+    // Regular expression representing an opening or closing tag of an HTML `<details>` element
+    static ref DETAILS_TAG_RE: Regex = Regex::new(r"(?i)<(/)?details[\s>]").unwrap();
+
+    // Regular expression representing an `id` attribute of an HTML element
+    static ref HTML_ID_RE: Regex =
+        Regex::new(r#"(?i)\sid\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'=<>`]+))"#).unwrap();
+
+    // Regular expression representing the opening tag of an HTML anchor (`<a>`) element,
+    // capturing its attributes
+    static ref HTML_ANCHOR_RE: Regex = Regex::new(r"(?i)<a(\s[^>]*)>").unwrap();
+
+    // Regular expression representing an `id` or `name` attribute of an HTML anchor element
+    static ref HTML_ANCHOR_ID_RE: Regex =
+        Regex::new(r#"(?i)\s(?:id|name)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'=<>`]+))"#)
+            .unwrap();
+}
+
 /// Returns an `Iterator` of [`Header`]s (a [`Headers`]) from a Markdown ([CommonMark]) string
 /// slice.
 ///
 /// # Allocations
 ///
 /// The underlying [Markdown parser] performs minimal allocations when processing the underlying
-/// string slice source, but once a header has been fully captured a `Header` is produced which
-/// allocates on the heap. The header's title and anchor link are normalized which involves string
-/// manipulation so therefore no longer necessarily corresponds to the underlying raw text in the
-/// slice. However, once the parser is complete or when the iterator is fully consumed, the source
-/// string slice and any other allocated resources will be reclaimed while still allowing the
-/// `Header`s to live.
+/// string slice source. Once a header has been fully captured a `Header` is produced which borrows
+/// from the source string slice where possible: a title which needs no normalization borrows the
+/// raw text directly, otherwise the normalized title is allocated on the heap. The anchor link is
+/// always generated and so is allocated. To decouple a `Header` from the lifetime of the source
+/// string slice, use [`Header::into_owned`].
+///
+/// # Front Matter
+///
+/// Any YAML or TOML front matter block at the start of the source is skipped, so that it isn't
+/// mistaken for a heading. See the [`front_matter`] function for more.
+///
+/// # Omitting Headers
+///
+/// A heading which contains an `<!-- omit in toc -->` (or `<!-- omit from toc -->`) HTML comment
+/// is not returned by the iterator. Similarly, a heading which contains an
+/// `<!-- omit subtree in toc -->` (or `<!-- omit subtree from toc -->`) HTML comment is not
+/// returned along with all of its sub-headings. An omitted heading still reserves its anchor link,
+/// so the anchor links of any later headings with the same title are unaffected.
+///
+/// # Anchor Elements
+///
+/// The ids (and names) of any HTML anchor elements in the source, such as
+/// `<a name="install"></a>`, are reserved so that no generated anchor link collides with them. A
+/// heading which embeds such an element, as in `## <a name="install"></a>Install`, uses it as its
+/// anchor link, as that is the target readers have already bookmarked.
 ///
 /// # Examples
 ///
@@ -45,43 +102,241 @@ use std::usize;
 ///
 /// [CommonMark]: https://commonmark.org/
 /// [`Header`]: struct.Header.html
+/// [`Header::into_owned`]: struct.Header.html#method.into_owned
 /// [`Headers`]: struct.Headers.html
+/// [`front_matter`]: fn.front_matter.html
 /// [Markdown parser]: https://docs.rs/pulldown-cmark/
+///
+/// Omitting a heading from the table of contents:
+///
+/// ```rust
+/// # use mtoc_parser::{headers, Header};
+/// let markdown = concat!(
+///     "# Contents <!-- omit in toc -->\n# Intro\n",
+///     "## Legal <!-- omit subtree in toc -->\n### Terms\n",
+/// );
+///
+/// let titles = headers(markdown).map(Header::into_title).collect::<Vec<_>>();
+///
+/// assert_eq!(vec!["Intro"], titles);
+/// ```
+///
+/// Using an embedded anchor element as the anchor link:
+///
+/// ```rust
+/// # use mtoc_parser::{headers, Header};
+/// let markdown = "# <a name=\"setup\"></a>Install\n# Setup\n";
+///
+/// let anchors = headers(markdown).map(Header::into_anchor).collect::<Vec<_>>();
+///
+/// assert_eq!(vec!["#setup", "#setup-1"], anchors);
+/// ```
 pub fn headers(buf: &str) -> Headers {
+    headers_with_options(buf, ParserOptions::default())
+}
+
+/// Returns an `Iterator` of [`Header`]s (a [`Headers`]) from a Markdown string slice, which is
+/// parsed with the given Markdown extensions enabled.
+///
+/// The [`headers`] function parses strict [CommonMark] with no extensions enabled. To process the
+/// headings the same way as the renderer of a document, enable the same extensions, for example
+/// with the [GitHub Flavored Markdown] preset of [`ParserOptions::gfm`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use mtoc_parser::{headers, headers_with_options, Inline, ParserOptions};
+/// let markdown = "# Old ~~and busted~~";
+///
+/// let commonmark = headers(markdown).next().unwrap();
+/// let gfm = headers_with_options(markdown, ParserOptions::gfm()).next().unwrap();
+///
+/// assert_eq!(&[Inline::Text("Old ~~and busted~~".into())], commonmark.inlines());
+/// assert_eq!(
+///     &[
+///         Inline::Text("Old ".into()),
+///         Inline::Strikethrough(vec![Inline::Text("and busted".into())]),
+///     ],
+///     gfm.inlines()
+/// );
+/// ```
+///
+/// [CommonMark]: https://commonmark.org/
+/// [GitHub Flavored Markdown]: https://github.github.com/gfm/
+/// [`Header`]: struct.Header.html
+/// [`Headers`]: struct.Headers.html
+/// [`ParserOptions::gfm`]: struct.ParserOptions.html#method.gfm
+/// [`headers`]: fn.headers.html
+pub fn headers_with_options(buf: &str, options: ParserOptions) -> Headers<'_> {
+    headers_with_slugger(buf, options, AnchorSlugger)
+}
+
+/// Returns an `Iterator` of [`Header`]s (a [`Headers`]) from a Markdown string slice, parsed with
+/// the given Markdown extensions and generating anchor links with the given [`Slugger`].
+///
+/// The [`headers`] and [`headers_with_options`] functions use the default [`AnchorSlugger`],
+/// which generates anchor links the same way as GitHub. A document which is rendered elsewhere
+/// can use a `Slugger` which follows the rules of its renderer instead.
+///
+/// # Examples
+///
+/// ```rust
+/// # use mtoc_parser::{headers_with_slugger, Header, ParserOptions, Slugger};
+/// struct Prefixed;
+///
+/// impl Slugger for Prefixed {
+///     fn slugify(&self, text: &str) -> String {
+///         format!("section-{}", text.to_lowercase().replace(' ', "-"))
+///     }
+/// }
+///
+/// let markdown = "# Getting Started\n# Getting Started\n";
+///
+/// let anchors = headers_with_slugger(markdown, ParserOptions::new(), Prefixed)
+///     .map(Header::into_anchor)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     vec!["#section-getting-started", "#section-getting-started-1"],
+///     anchors
+/// );
+/// ```
+///
+/// [`AnchorSlugger`]: struct.AnchorSlugger.html
+/// [`Header`]: struct.Header.html
+/// [`Headers`]: struct.Headers.html
+/// [`Slugger`]: trait.Slugger.html
+/// [`headers`]: fn.headers.html
+/// [`headers_with_options`]: fn.headers_with_options.html
+pub fn headers_with_slugger<'a, S>(buf: &'a str, options: ParserOptions, slugger: S) -> Headers<'a>
+where
+    S: Slugger + 'a,
+{
+    let body = front_matter(buf).map_or(0, |front_matter| front_matter.range().end);
+
     Headers {
-        slugger: AnchorSlugger::new(),
-        iter: Parser::new(buf).into_offset_iter(),
+        slugger: Box::new(slugger),
+        anchors: Anchors::new(),
+        iter: options.parser(&buf[body..]).into_offset_iter(),
         buf,
+        body,
+        cursor: LineCursor::new(),
+        parsed: false,
+        pending: VecDeque::new(),
+        omitted_level: None,
+        html_headings: false,
+        html: 0..0,
+        excluded: Vec::new(),
+        nesting: Nesting::default(),
+        emoji: Emoji::default(),
+        normalization: Normalization::default(),
     }
 }
 
 /// A heading entry from a parsed Markdown ([CommonMark]) document.
 ///
 /// `Header`s are produced via the [`headers`] function from parsing an underlying Markdown string
-/// slice. The following is captured for each Markdown header:
+/// slice, or can be built directly with [`Header::new`] or a [`HeaderBuilder`]. The following is
+/// captured for each Markdown header:
 ///
 /// - The headling level is recorded and accessible via the [`level`] method
-/// - The heading title is normalized and accessible via the [`title`] method
+/// - The heading title is normalized and accessible via the [`title`] method, and the raw
+///   heading text is accessible via the [`raw_title`] method
 /// - The heading anchor link is normalized and accessible via the [`anchor`] method
+/// - The structured inline content of the heading is accessible via the [`inlines`] method
+/// - The position of the raw heading text in the source is accessible via the [`range`],
+///   [`line`], and [`column`] methods
+/// - The span of the heading's section in the source is accessible via the [`section_range`]
+///   and [`shallow_section_range`] methods
 ///
+/// A `Header` which was built directly rather than parsed has no source, so its position and
+/// section methods return `None`.
+///
+/// The title and anchor link are stored as copy-on-write strings, so a parsed `Header` may borrow
+/// from its source string slice for the lifetime `'a`. Use [`into_owned`] to produce a `Header`
+/// with a `'static` lifetime.
+///
+/// [CommonMark]: https://commonmark.org/
+/// [`Header::new`]: #method.new
+/// [`HeaderBuilder`]: struct.HeaderBuilder.html
 /// [`anchor`]: #method.anchor
+/// [`column`]: #method.column
 /// [`headers`]: fn.headers.html
+/// [`inlines`]: #method.inlines
+/// [`into_owned`]: #method.into_owned
 /// [`level`]: #method.level
+/// [`line`]: #method.line
+/// [`range`]: #method.range
+/// [`raw_title`]: #method.raw_title
+/// [`section_range`]: #method.section_range
+/// [`shallow_section_range`]: #method.shallow_section_range
 /// [`title`]: #method.title
-#[derive(Debug, PartialEq)]
-pub struct Header {
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header<'a> {
     level: usize,
-    title: String,
-    anchor: String,
+    title: Cow<'a, str>,
+    raw_title: Cow<'a, str>,
+    anchor: Cow<'a, str>,
+    inlines: Vec<Inline<'a>>,
+    source: Option<Source>,
+}
+
+/// The location of a `Header` in the source string slice it was parsed from.
+#[derive(Clone, Debug, PartialEq)]
+struct Source {
+    range: Range<usize>,
+    line: usize,
+    column: usize,
+    section_range: Range<usize>,
+    shallow_section_range: Range<usize>,
 }
 
-impl fmt::Display for Header {
+impl<'a> fmt::Display for Header<'a> {
+    /// Formats the `Header` as a Markdown link to its anchor, escaping any characters in the title
+    /// which would otherwise break the link syntax.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "[{}]({})", self.title, self.anchor,)
+        write!(
+            f,
+            "[{}]({})",
+            normalize::escape_link_text(&self.title),
+            self.anchor,
+        )
     }
 }
 
-impl Header {
+impl<'a> Header<'a> {
+    /// Builds a new `Header` with the given level and title.
+    ///
+    /// The anchor link is generated from the title the same way as GitHub. Note that, unlike the
+    /// [`headers`] function, no effort is made to keep the anchor unique. To provide an explicit
+    /// anchor or another [`Slugger`], use a [`HeaderBuilder`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LevelError`] if the level is not between `1` and `6` (inclusive).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::Header;
+    /// let header = Header::new(2, "See Also").unwrap();
+    ///
+    /// assert_eq!(2, header.level());
+    /// assert_eq!("See Also", header.title());
+    /// assert_eq!("#see-also", header.anchor());
+    /// assert_eq!(None, header.range());
+    ///
+    /// assert!(Header::new(7, "Too Deep").is_err());
+    /// ```
+    ///
+    /// [`HeaderBuilder`]: struct.HeaderBuilder.html
+    /// [`LevelError`]: struct.LevelError.html
+    /// [`Slugger`]: trait.Slugger.html
+    /// [`headers`]: fn.headers.html
+    pub fn new<T: Into<Cow<'a, str>>>(level: usize, title: T) -> Result<Self, LevelError> {
+        HeaderBuilder::new(title).level(level).build()
+    }
+
     /// Returns the level of the header.
     ///
     /// # Examples
@@ -98,6 +353,10 @@ impl Header {
 
     /// Returns the normalized title of the header.
     ///
+    /// As the title is used as the text of a link in a table of contents, any links in the heading
+    /// are reduced to their link text and any images are reduced to their alternative text. To
+    /// get the heading text as it was written, use [`raw_title`].
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -105,13 +364,39 @@ impl Header {
     /// let header = headers("# <blink>A Title</blink>").next().unwrap();
     ///
     /// assert_eq!("A Title", header.title());
+    ///
+    /// let header = headers("# See [the spec](https://spec.example)").next().unwrap();
+    ///
+    /// assert_eq!("See the spec", header.title());
     /// ```
+    ///
+    /// [`raw_title`]: #method.raw_title
     pub fn title(&self) -> &str {
-        self.title.as_str()
+        &self.title
+    }
+
+    /// Returns the raw title of the header, as it was written in the source.
+    ///
+    /// For a `Header` which was not parsed from a source, the raw title is the same as its title.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let header = headers("# See [the spec](https://spec.example)").next().unwrap();
+    ///
+    /// assert_eq!("See [the spec](https://spec.example)", header.raw_title());
+    /// ```
+    pub fn raw_title(&self) -> &str {
+        &self.raw_title
     }
 
     /// Returns the normalized anchor link of the header.
     ///
+    /// If a parsed heading ends with an attribute block containing an explicit id (such as
+    /// `{#custom-id}`, as supported by Pandoc, kramdown and Hugo), the id is used as the anchor and
+    /// the attribute block is removed from the title.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -119,9 +404,138 @@ impl Header {
     /// let header = headers("# <blink>A Title</blink>").next().unwrap();
     ///
     /// assert_eq!("#a-title", header.anchor());
+    ///
+    /// let header = headers("# A Title {#custom-id}").next().unwrap();
+    ///
+    /// assert_eq!("A Title", header.title());
+    /// assert_eq!("#custom-id", header.anchor());
     /// ```
     pub fn anchor(&self) -> &str {
-        self.anchor.as_str()
+        &self.anchor
+    }
+
+    /// Returns the structured inline content of the header.
+    ///
+    /// For a `Header` which was not parsed from a source, the content is its title as plain text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Inline};
+    /// let header = headers("# The `main` function").next().unwrap();
+    ///
+    /// assert_eq!(Some(&Inline::Code("main".into())), header.inlines().get(1));
+    /// ```
+    pub fn inlines(&self) -> &[Inline<'a>] {
+        &self.inlines
+    }
+
+    /// Returns the byte range of the raw header text in the source string slice.
+    ///
+    /// The range covers the heading's text only, that is, it excludes any leading `#` markers,
+    /// surrounding whitespace and the line ending. Returns `None` if the header was not parsed
+    /// from a source.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let markdown = "# Title\n\n## A Section\n";
+    /// let header = headers(markdown).nth(1).unwrap();
+    ///
+    /// assert_eq!(Some(12..21), header.range());
+    /// assert_eq!("A Section", &markdown[header.range().unwrap()]);
+    /// ```
+    pub fn range(&self) -> Option<Range<usize>> {
+        self.source.as_ref().map(|source| source.range.clone())
+    }
+
+    /// Returns the line number of the raw header text in the source string slice.
+    ///
+    /// Line numbers start at `1`. Returns `None` if the header was not parsed from a source.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let header = headers("# Title\n\n## A Section\n").nth(1).unwrap();
+    ///
+    /// assert_eq!(Some(3), header.line());
+    /// ```
+    pub fn line(&self) -> Option<usize> {
+        self.source.as_ref().map(|source| source.line)
+    }
+
+    /// Returns the column number of the raw header text in the source string slice.
+    ///
+    /// Column numbers start at `1` and are counted in characters (i.e. Unicode scalar values)
+    /// rather than bytes. Returns `None` if the header was not parsed from a source.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let header = headers("# Title\n\n## A Section\n").nth(1).unwrap();
+    ///
+    /// assert_eq!(Some(4), header.column());
+    /// ```
+    pub fn column(&self) -> Option<usize> {
+        self.source.as_ref().map(|source| source.column)
+    }
+
+    /// Returns the byte range of the header's entire section in the source string slice.
+    ///
+    /// A section starts at the beginning of the heading and ends just before the next heading of
+    /// the same or a higher level (i.e. a level number less than or equal to this header's
+    /// level), or at the end of the source. The range therefore includes any nested
+    /// sub-sections. To exclude nested sub-sections, use [`shallow_section_range`]. Returns
+    /// `None` if the header was not parsed from a source.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let markdown = "# Title\n## Install\nRun it.\n### Linux\nYes.\n## Usage\n";
+    /// let header = headers(markdown).nth(1).unwrap();
+    ///
+    /// assert_eq!(
+    ///     "## Install\nRun it.\n### Linux\nYes.\n",
+    ///     &markdown[header.section_range().unwrap()]
+    /// );
+    /// ```
+    ///
+    /// [`shallow_section_range`]: #method.shallow_section_range
+    pub fn section_range(&self) -> Option<Range<usize>> {
+        self.source
+            .as_ref()
+            .map(|source| source.section_range.clone())
+    }
+
+    /// Returns the byte range of the header's section in the source string slice, excluding any
+    /// nested sub-sections.
+    ///
+    /// A shallow section starts at the beginning of the heading and ends just before the next
+    /// heading of any level, or at the end of the source. To include nested sub-sections, use
+    /// [`section_range`]. Returns `None` if the header was not parsed from a source.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let markdown = "# Title\n## Install\nRun it.\n### Linux\nYes.\n## Usage\n";
+    /// let header = headers(markdown).nth(1).unwrap();
+    ///
+    /// assert_eq!(
+    ///     "## Install\nRun it.\n",
+    ///     &markdown[header.shallow_section_range().unwrap()]
+    /// );
+    /// ```
+    ///
+    /// [`section_range`]: #method.section_range
+    pub fn shallow_section_range(&self) -> Option<Range<usize>> {
+        self.source
+            .as_ref()
+            .map(|source| source.shallow_section_range.clone())
     }
 
     /// Consumes this `Header`, returning the underlying normalized title.
@@ -135,7 +549,7 @@ impl Header {
     /// assert_eq!("A Title".to_string(), header.into_title());
     /// ```
     pub fn into_title(self) -> String {
-        self.title
+        self.title.into_owned()
     }
 
     /// Consumes this `Header`, returning the underlying normalized anchor.
@@ -149,7 +563,33 @@ impl Header {
     /// assert_eq!("#a-title".to_string(), header.into_anchor());
     /// ```
     pub fn into_anchor(self) -> String {
-        self.anchor
+        self.anchor.into_owned()
+    }
+
+    /// Consumes this `Header`, returning a new `Header` which owns its title and anchor link and
+    /// so no longer borrows from the source string slice.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Header};
+    /// fn first_header(markdown: &str) -> Option<Header<'static>> {
+    ///     headers(markdown).next().map(Header::into_owned)
+    /// }
+    ///
+    /// let header = first_header(&String::from("# A Title")).unwrap();
+    ///
+    /// assert_eq!("A Title", header.title());
+    /// ```
+    pub fn into_owned(self) -> Header<'static> {
+        Header {
+            level: self.level,
+            title: Cow::Owned(self.title.into_owned()),
+            raw_title: Cow::Owned(self.raw_title.into_owned()),
+            anchor: Cow::Owned(self.anchor.into_owned()),
+            inlines: inline::into_owned(self.inlines),
+            source: self.source,
+        }
     }
 
     /// Consumes this `Header`, returning a new `Header` with a level one number lower than the
@@ -173,8 +613,7 @@ impl Header {
             } else {
                 self.level - 1
             },
-            title: self.title,
-            anchor: self.anchor,
+            ..self
         }
     }
 
@@ -201,131 +640,1160 @@ impl Header {
             } else {
                 self.level + 1
             },
-            title: self.title,
-            anchor: self.anchor,
+            ..self
         }
     }
-}
-
-/// An iterator of [`Header`]s from an underlying Markdown string slice.
-///
-/// This `struct` is created by the [`headers`] function. See its documentation for more.
-///
-/// [`Header`]: struct.Header.html
-/// [`headers`]: fn.headers.html
-pub struct Headers<'a> {
-    slugger: AnchorSlugger,
-    iter: OffsetIter<'a>,
-    buf: &'a str,
-}
-
-impl<'a> Iterator for Headers<'a> {
-    type Item = Header;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let iter = &mut self.iter;
-        let mut state = State::NoHeader;
-
-        for (event, offset) in iter {
-            match event {
-                // Found the start of a new header. Capture its level and the ending offset which
-                // corresponds to the last character of the raw header text
-                Event::Start(Tag::Header(level)) => {
-                    state = State::FoundHeader(level, offset.end);
-                }
-                // Found the end of the header. Only enter if we're processing a header.
-                Event::End(Tag::Header(_)) if state.processing_header() => {
-                    // If no events were found between the start and end of a header then there is
-                    // no raw text and it is empty, so we'll set start offset to the end offset
-                    // (range length == 0)
-                    if let State::FoundHeader(level, end) = state {
-                        state = State::FoundRange(level, Range { start: end, end });
-                    }
 
-                    match state {
-                        State::FoundRange(level, range) => {
-                            let mut raw_header = self.buf.get(range).expect("range should exist");
-                            if raw_header.ends_with('\n') {
-                                raw_header = raw_header.get(..raw_header.len() - 1).unwrap();
-                            }
-                            if raw_header.ends_with('\r') {
-                                raw_header = raw_header.get(..raw_header.len() - 1).unwrap();
-                            }
+    /// Consumes this `Header`, returning a new `Header` with the given level.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LevelError`] if the level is not between `1` and `6` (inclusive).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let header = headers("# Heading").next().unwrap();
+    ///
+    /// assert_eq!(4, header.with_level(4).unwrap().level());
+    /// ```
+    ///
+    /// [`LevelError`]: struct.LevelError.html
+    pub fn with_level(self, level: usize) -> Result<Self, LevelError> {
+        Ok(Header {
+            level: check_level(level)?,
+            ..self
+        })
+    }
 
-                            let level = level.try_into().expect("level should not be negative");
-                            let title = normalize::titleize(raw_header);
-                            let anchor = format!("#{}", self.slugger.slug(raw_header));
+    /// Consumes this `Header`, returning a new `Header` with the given title.
+    ///
+    /// Note that the anchor link is left unchanged, and both the raw title and the inline content
+    /// are replaced with the title as plain text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let header = headers("# Heading").next().unwrap().with_title("Renamed");
+    ///
+    /// assert_eq!("Renamed", header.title());
+    /// assert_eq!("#heading", header.anchor());
+    /// ```
+    pub fn with_title<T: Into<Cow<'a, str>>>(self, title: T) -> Self {
+        let title = title.into();
 
-                            return Some(Header {
-                                level,
-                                title,
-                                anchor,
-                            });
-                        }
-                        _ => unreachable!("state can only be FoundRange"),
-                    }
-                }
-                // Found the first event inside a header. Capture its starting offset which
-                // corresponds to the first character of the raw header text
-                _ if state.found_header() => match state {
-                    State::FoundHeader(level, end) => {
-                        let range = Range {
-                            start: offset.start,
-                            end,
-                        };
-
-                        state = State::FoundRange(level, range);
-                    }
-                    _ => unreachable!("state can only be FoundHeader"),
-                },
-                // Skip all other events.
-                _ => (),
-            }
+        Header {
+            inlines: text_inlines(&title),
+            raw_title: title.clone(),
+            title,
+            ..self
         }
-        None
     }
-}
-
-#[derive(Debug)]
-enum State {
-    NoHeader,
-    FoundHeader(i32, usize),
-    FoundRange(i32, Range<usize>),
-}
 
-impl State {
-    fn found_header(&self) -> bool {
-        match *self {
-            State::FoundHeader(_, _) => true,
-            _ => false,
+    /// Consumes this `Header`, returning a new `Header` with the given anchor link.
+    ///
+    /// The anchor is used verbatim, so it can also point at another document.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let header = headers("# Heading").next().unwrap().with_anchor("other.md#heading");
+    ///
+    /// assert_eq!("[Heading](other.md#heading)", header.to_string());
+    /// ```
+    pub fn with_anchor<T: Into<Cow<'a, str>>>(self, anchor: T) -> Self {
+        Header {
+            anchor: anchor.into(),
+            ..self
         }
     }
 
-    fn processing_header(&self) -> bool {
+    /// Returns the starting offset of a parsed header's section.
+    fn section_start(&self) -> usize {
+        self.source
+            .as_ref()
+            .expect("parsed header should have a source")
+            .section_range
+            .start
+    }
+
+    /// Returns the mutable source location of a parsed header.
+    fn source_mut(&mut self) -> &mut Source {
+        self.source
+            .as_mut()
+            .expect("parsed header should have a source")
+    }
+}
+
+/// A builder for a [`Header`] which was not parsed from a Markdown document.
+///
+/// This is useful for adding entries to a table of contents which come from another source,
+/// such as a different document or a synthetic "see also" entry.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```rust
+/// use mtoc_parser::HeaderBuilder;
+///
+/// let header = HeaderBuilder::new("Related Projects")
+///     .level(2)
+///     .anchor("https://example.com/related")
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(2, header.level());
+/// assert_eq!("https://example.com/related", header.anchor());
+/// ```
+///
+/// Combining parsed and built `Header`s when formatting a table of contents:
+///
+/// ```rust
+/// use mtoc_parser::{headers, Format, Formatter, HeaderBuilder};
+/// use std::iter;
+/// use std::str;
+///
+/// let see_also = HeaderBuilder::new("See Also")
+///     .anchor("other.md#see-also")
+///     .build()
+///     .unwrap();
+/// let mut output = Vec::new();
+///
+/// Formatter::default()
+///     .fmt(&mut output, headers("# Intro").chain(iter::once(see_also)))
+///     .unwrap();
+///
+/// assert_eq!(
+///     "- [Intro](#intro)\n- [See Also](other.md#see-also)\n",
+///     str::from_utf8(&output).unwrap()
+/// );
+/// ```
+///
+/// [`Header`]: struct.Header.html
+#[derive(Debug)]
+pub struct HeaderBuilder<'a, S = AnchorSlugger> {
+    level: usize,
+    title: Cow<'a, str>,
+    anchor: Option<Cow<'a, str>>,
+    slugger: S,
+}
+
+impl<'a> HeaderBuilder<'a> {
+    /// Builds a new `HeaderBuilder` with the given title.
+    ///
+    /// The default level is `1` and the default anchor link is generated from the title the same
+    /// way as GitHub.
+    pub fn new<T: Into<Cow<'a, str>>>(title: T) -> Self {
+        HeaderBuilder {
+            level: 1,
+            title: title.into(),
+            anchor: None,
+            slugger: AnchorSlugger,
+        }
+    }
+}
+
+impl<'a, S: Slugger> HeaderBuilder<'a, S> {
+    /// Sets the level of the header.
+    ///
+    /// The level is checked when [`build`] is called.
+    ///
+    /// [`build`]: #method.build
+    pub fn level(mut self, level: usize) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets an explicit anchor link for the header, which is used verbatim.
+    ///
+    /// If no anchor is set, one is generated by normalizing the title.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use mtoc_parser::HeaderBuilder;
+    ///
+    /// let header = HeaderBuilder::new("Setup").anchor("#install").build().unwrap();
+    ///
+    /// assert_eq!("[Setup](#install)", header.to_string());
+    /// ```
+    pub fn anchor<T: Into<Cow<'a, str>>>(mut self, anchor: T) -> Self {
+        self.anchor = Some(anchor.into());
+        self
+    }
+
+    /// Sets the [`Slugger`] which generates the anchor link from the title.
+    ///
+    /// The default is an [`AnchorSlugger`], which generates anchor links the same way as GitHub.
+    /// The `Slugger` is not used when an explicit [`anchor`] is set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use mtoc_parser::{Flavor, HeaderBuilder};
+    ///
+    /// let header = HeaderBuilder::new("Getting Started")
+    ///     .slugger(Flavor::Bitbucket)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!("#markdown-header-getting-started", header.anchor());
+    /// ```
+    ///
+    /// [`AnchorSlugger`]: struct.AnchorSlugger.html
+    /// [`Slugger`]: trait.Slugger.html
+    /// [`anchor`]: #method.anchor
+    pub fn slugger<T: Slugger>(self, slugger: T) -> HeaderBuilder<'a, T> {
+        HeaderBuilder {
+            level: self.level,
+            title: self.title,
+            anchor: self.anchor,
+            slugger,
+        }
+    }
+
+    /// Builds the `Header`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`LevelError`] if the level is not between `1` and `6` (inclusive).
+    ///
+    /// [`LevelError`]: struct.LevelError.html
+    pub fn build(self) -> Result<Header<'a>, LevelError> {
+        let level = check_level(self.level)?;
+        let title = self.title;
+        let anchor = match self.anchor {
+            Some(anchor) => anchor,
+            None => {
+                let text = normalize::normalize_unicode(
+                    Cow::Owned(normalize::html_text(&title)),
+                    Normalization::default(),
+                );
+
+                Cow::Owned(format!("#{}", self.slugger.slugify(&text)))
+            }
+        };
+
+        Ok(Header {
+            level,
+            inlines: text_inlines(&title),
+            raw_title: title.clone(),
+            title,
+            anchor,
+            source: None,
+        })
+    }
+}
+
+/// Returns the inline content of a title as plain text.
+fn text_inlines<'a>(title: &Cow<'a, str>) -> Vec<Inline<'a>> {
+    if title.is_empty() {
+        Vec::new()
+    } else {
+        vec![Inline::Text(title.clone())]
+    }
+}
+
+/// An error returned when a header level is out of range.
+///
+/// Valid header levels are between `1` and `6` (inclusive), according to the [ATX headings
+/// spec].
+///
+/// [ATX headings spec]: https://spec.commonmark.org/0.29/#atx-headings
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelError {
+    level: usize,
+}
+
+impl LevelError {
+    /// Returns the invalid level.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "header level must be between 1 and 6, got {}",
+            self.level
+        )
+    }
+}
+
+impl error::Error for LevelError {}
+
+fn check_level(level: usize) -> Result<usize, LevelError> {
+    match level {
+        1..=6 => Ok(level),
+        _ => Err(LevelError { level }),
+    }
+}
+
+/// An iterator of [`Header`]s from an underlying Markdown string slice.
+///
+/// This `struct` is created by the [`headers`] function. See its documentation for more.
+///
+/// The iterator parses the whole source when the first `Header` is requested, holding the headers
+/// until they are returned by subsequent calls. This ensures that the explicit ids of headings and
+/// the ids of HTML anchor elements anywhere in the document are reserved before any anchor link is
+/// generated, and that each header's section can end where the next header of the same or a
+/// higher level begins.
+///
+/// [`Header`]: struct.Header.html
+/// [`headers`]: fn.headers.html
+pub struct Headers<'a> {
+    slugger: Box<dyn Slugger + 'a>,
+    anchors: Anchors,
+    iter: OffsetIter<'a>,
+    buf: &'a str,
+    // The offset of the document body in the source, following any front matter, which is where
+    // the parser starts
+    body: usize,
+    cursor: LineCursor,
+    // Whether the source has been parsed into `pending`
+    parsed: bool,
+    pending: VecDeque<(Header<'a>, Option<Omit>)>,
+    omitted_level: Option<usize>,
+    html_headings: bool,
+    // The span of the current raw HTML block which has not yet been scanned for headings
+    html: Range<usize>,
+    excluded: Vec<Container>,
+    nesting: Nesting,
+    emoji: Emoji,
+    normalization: Normalization,
+}
+
+/// A block container in a Markdown document which can hold headings.
+///
+/// Headings nested in a container are returned by [`Headers`] unless the kind of container is
+/// excluded with [`Headers::exclude`].
+///
+/// [`Headers`]: struct.Headers.html
+/// [`Headers::exclude`]: struct.Headers.html#method.exclude
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Container {
+    /// A block quote, such as `> # Quoted`.
+    BlockQuote,
+    /// A list item, such as `- # Listed`.
+    List,
+    /// A raw HTML `<details>` element, whose content is collapsed by default.
+    Details,
+}
+
+/// The number of block containers of each kind which enclose the current position in the source.
+#[derive(Debug, Default)]
+struct Nesting {
+    block_quotes: usize,
+    lists: usize,
+    details: usize,
+}
+
+impl Nesting {
+    fn depth(&self, container: Container) -> usize {
+        match container {
+            Container::BlockQuote => self.block_quotes,
+            Container::List => self.lists,
+            Container::Details => self.details,
+        }
+    }
+
+    /// Tracks the opening and closing `<details>` tags in a raw HTML block (or a line of one).
+    fn track_details(&mut self, html: &str) {
+        for caps in DETAILS_TAG_RE.captures_iter(html) {
+            if caps.get(1).is_some() {
+                self.details = self.details.saturating_sub(1);
+            } else {
+                self.details += 1;
+            }
+        }
+    }
+}
+
+/// How a heading is omitted from the table of contents by an annotation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Omit {
+    Header,
+    Subtree,
+}
+
+impl<'a> Iterator for Headers<'a> {
+    type Item = Header<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.parsed {
+            self.parse();
+        }
+
+        let mut header = loop {
+            let (header, omit) = self.pending.pop_front()?;
+            if !self.is_omitted(&header, omit) {
+                break header;
+            }
+        };
+
+        // The shallow section ends where the next header of any level begins
+        if let Some((next, _)) = self.pending.front() {
+            header.source_mut().shallow_section_range.end = next.section_start();
+        }
+
+        // The full section ends where the next header of the same or higher level begins, past
+        // all nested sub-headers
+        let level = header.level;
+        if let Some((next, _)) = self.pending.iter().find(|(next, _)| next.level <= level) {
+            header.source_mut().section_range.end = next.section_start();
+        }
+
+        Some(header)
+    }
+}
+
+impl<'a> Headers<'a> {
+    /// Sets whether raw HTML heading elements (`<h1>` to `<h6>`) are recognized as headers.
+    ///
+    /// This is disabled by default. When enabled, an HTML block containing a heading element such
+    /// as `<h2 align="center" id="usage">Usage</h2>` produces a `Header` whose title is the text
+    /// content of the element. The anchor link is the element's `id` attribute if present,
+    /// otherwise it is generated from the title in the same manner as a Markdown heading. The ids
+    /// of all HTML heading elements are reserved, so no generated anchor link will collide with
+    /// them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers};
+    /// let markdown = "<h1 align=\"center\">My Project</h1>\n\n<h2 id=\"usage\">Usage</h2>\n";
+    ///
+    /// assert_eq!(0, headers(markdown).count());
+    ///
+    /// let anchors = headers(markdown)
+    ///     .html_headings(true)
+    ///     .map(|header| header.to_string())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(vec!["[My Project](#my-project)", "[Usage](#usage)"], anchors);
+    /// ```
+    pub fn html_headings(mut self, enabled: bool) -> Self {
+        self.html_headings = enabled;
+        self
+    }
+
+    /// Excludes any headings nested in the given kind of [`Container`].
+    ///
+    /// By default, headings are returned regardless of their container. An excluded heading still
+    /// reserves its anchor link, so the anchor links of any later headings with the same title are
+    /// unaffected. This method can be called more than once to exclude several kinds of container.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Container, Header};
+    /// let markdown = "# Usage\n> ## Quoted\n- ## Listed\n# Help\n";
+    ///
+    /// let titles = headers(markdown)
+    ///     .exclude(Container::BlockQuote)
+    ///     .exclude(Container::List)
+    ///     .map(Header::into_title)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(vec!["Usage", "Help"], titles);
+    /// ```
+    ///
+    /// [`Container`]: enum.Container.html
+    pub fn exclude(mut self, container: Container) -> Self {
+        if !self.excluded.contains(&container) {
+            self.excluded.push(container);
+        }
+        self
+    }
+
+    /// Sets how emoji are written in the title of each header.
+    ///
+    /// By default, emoji are kept as written. The anchor links of headers are unaffected by this
+    /// setting. See [`Emoji`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Emoji};
+    /// let header = headers("## :sparkles: Features").emoji(Emoji::Unicode).next().unwrap();
+    ///
+    /// assert_eq!("[✨ Features](#sparkles-features)", header.to_string());
+    /// ```
+    ///
+    /// [`Emoji`]: enum.Emoji.html
+    pub fn emoji(mut self, emoji: Emoji) -> Self {
+        self.emoji = emoji;
+        self
+    }
+
+    /// Sets the Unicode normalization form applied to the title and anchor link of each header.
+    ///
+    /// By default, the NFC form is applied. Note that explicit ids (such as `{#custom-id}`) are
+    /// always used verbatim. See [`Normalization`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Normalization};
+    /// let header = headers("## Ｗｉｄｅ Text")
+    ///     .normalization(Normalization::Nfkc)
+    ///     .next()
+    ///     .unwrap();
+    ///
+    /// assert_eq!("[Wide Text](#wide-text)", header.to_string());
+    /// ```
+    ///
+    /// [`Normalization`]: enum.Normalization.html
+    pub fn normalization(mut self, form: Normalization) -> Self {
+        self.normalization = form;
+        self
+    }
+
+    /// Returns whether a `Header` is omitted from the iterator, either by its own annotation or by
+    /// the annotation of an ancestor which omits its subtree.
+    fn is_omitted(&mut self, header: &Header<'_>, omit: Option<Omit>) -> bool {
+        if let Some(level) = self.omitted_level {
+            if header.level > level {
+                return true;
+            }
+            self.omitted_level = None;
+        }
+
+        match omit {
+            Some(Omit::Header) => true,
+            Some(Omit::Subtree) => {
+                self.omitted_level = Some(header.level);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Parses every `Header` in the source into `pending` along with its omit annotation, if any.
+    ///
+    /// The explicit ids of headings and the ids of HTML anchor elements are reserved as they are
+    /// parsed, so the remaining anchor links are generated once the whole source has been parsed.
+    /// The section ranges of each `Header` extend to the end of the source and are narrowed when
+    /// it is returned. An omitted `Header` is still held, as it reserves its anchor link and ends
+    /// the sections of its preceding headers.
+    fn parse(&mut self) {
+        let mut parsed = Vec::new();
+        while let Some(header) = self.parse_header() {
+            let excluded = self.is_excluded();
+            parsed.push((header, excluded));
+        }
+        self.parsed = true;
+
+        for (mut parsed, excluded) in parsed {
+            if let Some(text) = parsed.slug_text {
+                parsed.header.anchor = Cow::Owned(self.anchor(&text));
+            }
+            // A heading nested in an excluded container has reserved its anchor link, but is
+            // otherwise skipped so that it doesn't end the sections of its preceding headers
+            if !excluded {
+                self.pending.push_back((parsed.header, parsed.omit));
+            }
+        }
+    }
+
+    /// Returns whether the current position in the source is nested in an excluded container.
+    fn is_excluded(&self) -> bool {
+        self.excluded
+            .iter()
+            .any(|&container| self.nesting.depth(container) > 0)
+    }
+
+    /// Parses and returns the next `Header` in the source, regardless of its container.
+    fn parse_header(&mut self) -> Option<Parsed<'a>> {
+        // A single HTML block can contain more than one heading element
+        if let Some(header) = self.next_html_header() {
+            return Some(header);
+        }
+
+        let mut state = State::NoHeader;
+        let mut inlines = InlineCollector::new();
+        let mut links = LinkReducer::default();
+
+        while let Some((event, offset)) = self.iter.next() {
+            let offset = offset.start + self.body..offset.end + self.body;
+            if let Event::Html(html) | Event::InlineHtml(html) = &event {
+                self.anchors.reserve_html_anchors(html);
+            }
+            match event {
+                // Found the start of a new header. Capture its level, its starting offset, and the
+                // ending offset which corresponds to the last character of the raw header text,
+                // not including any line ending. An empty ATX header is reported with an ending
+                // offset before its starting offset, so the end of its line is used instead
+                Event::Start(Tag::Header(level)) => {
+                    let end = if offset.end < offset.start {
+                        line_end(self.buf, offset.start)
+                    } else {
+                        offset.end
+                    };
+                    let end = trim_eol(self.buf, end);
+                    // A setext header spans more than one line, the last of which is its
+                    // underline rather than part of its raw text
+                    let end = match self.buf[offset.start..end].rfind('\n') {
+                        Some(idx) => trim_eol(self.buf, offset.start + idx + 1),
+                        None => end,
+                    };
+                    state = State::FoundHeader(level, offset.start, end);
+                }
+                // Found the end of the header. Only enter if we're processing a header.
+                Event::End(Tag::Header(_)) if state.processing_header() => {
+                    // If no events were found between the start and end of a header then there is
+                    // no raw text and it is empty, so we'll set start offset to the end offset
+                    // (range length == 0)
+                    if let State::FoundHeader(level, start, end) = state {
+                        state = State::FoundRange(level, start, Range { start: end, end });
+                    }
+
+                    match state {
+                        State::FoundRange(level, start, range) => {
+                            let raw_header =
+                                self.buf.get(range.clone()).expect("range should exist");
+
+                            let level = level.try_into().expect("level should not be negative");
+                            let mut inlines = inlines.finish();
+                            let mut id = None;
+                            let mut title_range = Range {
+                                start: range.start,
+                                end: links.content_end().max(range.start),
+                            };
+                            if let Some((text, explicit_id)) =
+                                normalize::split_attributes(raw_header)
+                            {
+                                // The attribute block is only honored if it also ends the text
+                                // content, which rules out a block inside a code span
+                                let block = raw_header[text.len()..].trim();
+                                if strip_attributes(&mut inlines, block) {
+                                    id = explicit_id;
+                                    title_range.end = title_range.end.min(range.start + text.len());
+                                }
+                            }
+                            let anchor_id = embedded_anchor(&inlines);
+                            let title = match links.reduce(self.buf, title_range) {
+                                Cow::Borrowed(text) => {
+                                    normalize::titleize(text, self.normalization)
+                                }
+                                Cow::Owned(text) => Cow::Owned(
+                                    normalize::titleize(&text, self.normalization).into_owned(),
+                                ),
+                            };
+                            let title = normalize::emojify(title, self.emoji);
+                            let mut slug_text = None;
+                            let anchor = match id.or(anchor_id) {
+                                Some(id) => Cow::Owned(self.anchors.explicit_anchor(id)),
+                                None => {
+                                    slug_text = Some(inline::text_content(&inlines));
+                                    Cow::Borrowed("")
+                                }
+                            };
+                            let (line, column) = self.cursor.advance(self.buf, range.start);
+                            let section_range = Range {
+                                start,
+                                end: self.buf.len(),
+                            };
+
+                            let omit = omit_annotation(&inlines);
+
+                            return Some(Parsed {
+                                header: Header {
+                                    level,
+                                    title,
+                                    raw_title: Cow::Borrowed(raw_header),
+                                    anchor,
+                                    inlines,
+                                    source: Some(Source {
+                                        range,
+                                        line,
+                                        column,
+                                        shallow_section_range: section_range.clone(),
+                                        section_range,
+                                    }),
+                                },
+                                omit,
+                                slug_text,
+                            });
+                        }
+                        _ => unreachable!("state can only be FoundRange"),
+                    }
+                }
+                // Found the first event inside a header. Capture its starting offset which
+                // corresponds to the first character of the raw header text
+                event if state.found_header() => {
+                    match state {
+                        State::FoundHeader(level, start, end) => {
+                            let range = Range {
+                                start: event_start(self.buf, &event, &offset),
+                                end,
+                            };
+
+                            state = State::FoundRange(level, start, range);
+                        }
+                        _ => unreachable!("state can only be FoundHeader"),
+                    }
+                    links.push(self.buf, &event, &offset);
+                    inlines.push(event);
+                }
+                // Found a subsequent event inside a header. Collect it as inline content
+                event if state.processing_header() => {
+                    links.push(self.buf, &event, &offset);
+                    inlines.push(event);
+                }
+                // Track the block containers which enclose any following headings
+                Event::Start(Tag::BlockQuote) => self.nesting.block_quotes += 1,
+                Event::End(Tag::BlockQuote) => self.nesting.block_quotes -= 1,
+                Event::Start(Tag::List(_)) => self.nesting.lists += 1,
+                Event::End(Tag::List(_)) => self.nesting.lists -= 1,
+                // Found a raw HTML block (or a line of one). Track any `<details>` elements and
+                // scan it for heading elements, skipping any part which was covered by a previous
+                // heading element
+                Event::Html(html) => {
+                    self.nesting.track_details(&html);
+                    if self.html_headings {
+                        self.html = self.html.end.max(offset.start)..offset.end;
+                        if let Some(header) = self.next_html_header() {
+                            return Some(header);
+                        }
+                    }
+                }
+                // Skip all other events.
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Parses and returns the next `Header` from an HTML heading element in the unscanned span of
+    /// the current raw HTML block, if any.
+    fn next_html_header(&mut self) -> Option<Parsed<'a>> {
+        let buf = self.buf;
+        while self.html.start < self.html.end {
+            let start = match HTML_HEADING_START_RE.find(&buf[self.html.clone()]) {
+                Some(tag) => self.html.start + tag.start(),
+                None => {
+                    self.html.start = self.html.end;
+                    return None;
+                }
+            };
+
+            match html_heading(&buf[start..]) {
+                Some(caps) => {
+                    let end = start + caps.get(0).unwrap().end();
+                    // A multi-line element is skipped when scanning the following lines
+                    self.html = end..self.html.end.max(end);
+
+                    let content = caps.get(3).unwrap();
+                    let level = caps[1].parse().expect("level should be a digit");
+                    let range = Range {
+                        start: start + content.start(),
+                        end: start + content.end(),
+                    };
+                    let id = caps
+                        .get(2)
+                        .and_then(|attrs| html_id(attrs.as_str()))
+                        .or_else(|| html_anchor_id(content.as_str()))
+                        .filter(|id| !id.is_empty());
+
+                    return Some(self.html_header(level, start, range, id));
+                }
+                None => self.html.start = start + 1,
+            }
+        }
+        None
+    }
+
+    /// Returns a unique anchor link for the rendered text content of a heading, which is slugified
+    /// by the `Slugger` once normalized to the Unicode normalization form.
+    fn anchor(&mut self, text: &str) -> String {
+        let text = normalize::normalize_unicode(Cow::Borrowed(text), self.normalization);
+        let slug = self.slugger.slugify(&text);
+
+        self.anchors.unique_anchor(&*self.slugger, slug)
+    }
+
+    /// Builds a `Header` from the content of an HTML heading element found at `start`.
+    fn html_header(
+        &mut self,
+        level: usize,
+        start: usize,
+        range: Range<usize>,
+        id: Option<&str>,
+    ) -> Parsed<'a> {
+        let raw_header = self.buf.get(range.clone()).expect("range should exist");
+        let title = normalize::titleize(raw_header, self.normalization);
+        let inlines = text_inlines(&title);
+        let title = normalize::emojify(title, self.emoji);
+        let (anchor, slug_text) = match id {
+            Some(id) => (Cow::Owned(self.anchors.explicit_anchor(id)), None),
+            None => (Cow::Borrowed(""), Some(normalize::html_text(raw_header))),
+        };
+        let (line, column) = self.cursor.advance(self.buf, range.start);
+        let section_range = Range {
+            start,
+            end: self.buf.len(),
+        };
+
+        Parsed {
+            header: Header {
+                level,
+                inlines,
+                title,
+                raw_title: Cow::Borrowed(raw_header),
+                anchor,
+                source: Some(Source {
+                    range,
+                    line,
+                    column,
+                    shallow_section_range: section_range.clone(),
+                    section_range,
+                }),
+            },
+            omit: None,
+            slug_text,
+        }
+    }
+}
+
+/// A `Header` parsed from the source, which is held until its anchor link can be generated.
+#[derive(Debug)]
+struct Parsed<'a> {
+    header: Header<'a>,
+    omit: Option<Omit>,
+    // The text content which the anchor link is generated from, or `None` if the header has an
+    // explicit id
+    slug_text: Option<String>,
+}
+
+#[derive(Debug)]
+enum State {
+    NoHeader,
+    FoundHeader(i32, usize, usize),
+    FoundRange(i32, usize, Range<usize>),
+}
+
+impl State {
+    fn found_header(&self) -> bool {
         match *self {
-            State::FoundHeader(_, _) | State::FoundRange(_, _) => true,
+            State::FoundHeader(_, _, _) => true,
             _ => false,
         }
     }
+
+    fn processing_header(&self) -> bool {
+        match *self {
+            State::FoundHeader(_, _, _) | State::FoundRange(_, _, _) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Returns the omit annotation found in the inline content of a heading, if any.
+fn omit_annotation(inlines: &[Inline<'_>]) -> Option<Omit> {
+    inlines.iter().find_map(|inline| match inline {
+        Inline::Html(html) => OMIT_RE.captures(html.trim()).map(|caps| {
+            if caps.get(1).is_some() {
+                Omit::Subtree
+            } else {
+                Omit::Header
+            }
+        }),
+        _ => None,
+    })
+}
+
+/// Removes a trailing attribute block from the inline content of a heading, returning whether the
+/// block was found at the end of the content.
+fn strip_attributes(inlines: &mut Vec<Inline<'_>>, block: &str) -> bool {
+    let len = match inlines.last() {
+        Some(Inline::Text(text)) => {
+            let text = text.trim_end();
+            if !text.ends_with(block) {
+                return false;
+            }
+            text[..text.len() - block.len()].trim_end().len()
+        }
+        _ => return false,
+    };
+
+    if len == 0 {
+        inlines.pop();
+    } else if let Some(Inline::Text(text)) = inlines.last_mut() {
+        if let Cow::Borrowed(borrowed) = *text {
+            *text = Cow::Borrowed(&borrowed[..len]);
+        } else {
+            text.to_mut().truncate(len);
+        }
+    }
+    true
+}
+
+/// Returns the offset of the end of the line containing `start`, including its line ending.
+fn line_end(buf: &str, start: usize) -> usize {
+    buf[start..]
+        .find('\n')
+        .map_or(buf.len(), |idx| start + idx + 1)
+}
+
+/// Returns the captures of an HTML heading element at the start of the given text, if the element
+/// is closed by a matching tag within the same raw HTML block (that is, before any blank line).
+fn html_heading(text: &str) -> Option<Captures<'_>> {
+    HTML_HEADING_RE
+        .captures(text)
+        .filter(|caps| caps[1] == caps[4] && !has_blank_line(&caps[3]))
+}
+
+/// Returns the value of the `id` attribute in the attributes of an HTML element, if any.
+fn html_id(attrs: &str) -> Option<&str> {
+    attribute_value(&HTML_ID_RE, attrs)
+}
+
+/// Returns the value of the `id` (or otherwise, `name`) attribute of the first HTML anchor element
+/// in the given HTML which has one, if any.
+fn html_anchor_id(html: &str) -> Option<&str> {
+    HTML_ANCHOR_RE
+        .captures_iter(html)
+        .find_map(|caps| attribute_value(&HTML_ANCHOR_ID_RE, caps.get(1)?.as_str()))
 }
 
+/// Returns the value of the first attribute matched by the given regular expression, whether it
+/// is double-quoted, single-quoted or unquoted.
+fn attribute_value<'a>(re: &Regex, attrs: &'a str) -> Option<&'a str> {
+    re.captures(attrs).and_then(|caps| {
+        caps.get(1)
+            .or_else(|| caps.get(2))
+            .or_else(|| caps.get(3))
+            .map(|value| value.as_str())
+    })
+}
+
+/// Returns the id of an HTML anchor element (such as `<a name="install"></a>`) embedded in the
+/// inline content of a heading, which is the target readers already link to.
+fn embedded_anchor<'a>(inlines: &'a [Inline<'_>]) -> Option<&'a str> {
+    inlines.iter().find_map(|inline| match inline {
+        Inline::Html(html) => html_anchor_id(html).filter(|id| !id.is_empty()),
+        Inline::Emphasis(children) | Inline::Strong(children) | Inline::Strikethrough(children) => {
+            embedded_anchor(children)
+        }
+        _ => None,
+    })
+}
+
+/// Returns whether the text contains a blank line, which would end a raw HTML block.
+fn has_blank_line(text: &str) -> bool {
+    text.lines().skip(1).any(|line| line.trim().is_empty())
+}
+
+/// Returns the given ending offset, moved before any "newline" or "carriage return/newline" line
+/// ending.
+fn trim_eol(buf: &str, mut end: usize) -> usize {
+    let bytes = buf.as_bytes();
+    if end > 0 && bytes[end - 1] == b'\n' {
+        end -= 1;
+    }
+    if end > 0 && bytes[end - 1] == b'\r' {
+        end -= 1;
+    }
+    end
+}
+
+/// Tracks the line number of a position in a source string slice.
+///
+/// As `Header`s are produced in document order, the cursor almost always moves forward so that each
+/// byte of the source is scanned for line endings at most once. Should an offset ever be behind
+/// the cursor, the source is scanned again from its start.
 #[derive(Debug)]
-struct AnchorSlugger(HashSet<String>);
+struct LineCursor {
+    offset: usize,
+    line: usize,
+    line_start: usize,
+}
 
-impl AnchorSlugger {
+impl LineCursor {
     fn new() -> Self {
-        AnchorSlugger(HashSet::new())
+        LineCursor {
+            offset: 0,
+            line: 1,
+            line_start: 0,
+        }
     }
 
-    fn slug(&mut self, text: &str) -> &str {
-        self.unique_slug(normalize::slugify(text))
+    /// Moves the cursor forward to `offset`, returning the line and column numbers at `offset`.
+    fn advance(&mut self, buf: &str, offset: usize) -> (usize, usize) {
+        if offset < self.offset {
+            *self = LineCursor::new();
+        }
+
+        let scanned = &buf[self.offset..offset];
+        for (idx, _) in scanned.match_indices('\n') {
+            self.line += 1;
+            self.line_start = self.offset + idx + 1;
+        }
+        self.offset = offset;
+
+        let column = buf[self.line_start..offset].chars().count() + 1;
+
+        (self.line, column)
     }
+}
+
+/// Tracks the source spans of the link syntax inside a heading so that the title, which is
+/// otherwise kept as written, can reduce each link to its link text and each image to its
+/// alternative text. Footnote references are removed entirely.
+///
+/// The parser only reports the position of the opening `[` of a link or image, so the rest of its
+/// syntax (such as `](#url)`) is taken to run from the end of its content to the start of whatever
+/// follows it.
+#[derive(Debug, Default)]
+struct LinkReducer {
+    /// The end of the content seen so far.
+    end: usize,
+    /// The start of the syntax which closes a link, image or footnote reference, which runs to the
+    /// start of the next event.
+    tail: Option<usize>,
+    removals: Vec<Range<usize>>,
+}
+
+impl LinkReducer {
+    fn push(&mut self, buf: &str, event: &Event<'_>, offset: &Range<usize>) {
+        match event {
+            // An autolink's content is everything between its angle brackets
+            Event::Start(Tag::Link(LinkType::Autolink, _, _))
+            | Event::Start(Tag::Link(LinkType::Email, _, _)) => {
+                self.close_tail(offset.start);
+                self.removals.push(offset.start..offset.start + 1);
+                self.removals.push(offset.end - 1..offset.end);
+                self.end = offset.end;
+            }
+            Event::End(Tag::Link(LinkType::Autolink, _, _))
+            | Event::End(Tag::Link(LinkType::Email, _, _)) => (),
+            // The content starts after the opening `[` of a link or the opening `![` of an image
+            Event::Start(Tag::Link(_, _, _)) | Event::Start(Tag::Image(_, _, _)) => {
+                let marker = match event {
+                    Event::Start(Tag::Image(_, _, _)) => 2,
+                    _ => 1,
+                };
+                self.close_tail(offset.start);
+                self.removals.push(offset.start..offset.start + marker);
+                self.end = offset.start + marker;
+            }
+            // The closing syntax of a nested link or image runs on into that of its parent
+            Event::End(Tag::Link(_, _, _)) | Event::End(Tag::Image(_, _, _)) => {
+                if self.tail.is_none() {
+                    self.tail = Some(self.end);
+                }
+            }
+            Event::FootnoteReference(_) => {
+                self.close_tail(offset.start);
+                self.tail = Some(offset.start);
+            }
+            _ => {
+                self.close_tail(event_start(buf, event, offset));
+                self.end = self.end.max(offset.end);
+            }
+        }
+    }
+
+    /// Removes the pending closing syntax of a link, image or footnote reference, which ends at
+    /// the given offset.
+    fn close_tail(&mut self, end: usize) {
+        if let Some(start) = self.tail.take() {
+            self.removals.push(start..end);
+        }
+    }
+
+    /// Returns the end of the content of the heading, which excludes the closing syntax of a link,
+    /// image or footnote reference at its end.
+    fn content_end(&self) -> usize {
+        self.tail.unwrap_or(self.end)
+    }
+
+    /// Returns the source text in the given range with the link syntax removed, borrowing from the
+    /// source if no links, images or footnote references were found.
+    fn reduce<'a>(&self, buf: &'a str, range: Range<usize>) -> Cow<'a, str> {
+        if self.removals.is_empty() {
+            return Cow::Borrowed(buf.get(range).expect("range should exist"));
+        }
+
+        let mut reduced = String::with_capacity(range.len());
+        let mut cursor = range.start;
+        for removal in &self.removals {
+            let start = removal.start.min(range.end);
+            if start > cursor {
+                reduced.push_str(&buf[cursor..start]);
+            }
+            cursor = cursor.max(removal.end);
+        }
+        if range.end > cursor {
+            reduced.push_str(&buf[cursor..range.end]);
+        }
+
+        Cow::Owned(reduced)
+    }
+}
 
-    fn unique_slug(&mut self, mut candidate: String) -> &str {
+/// Returns the offset in the source where an event inside a heading starts.
+///
+/// The parser reports the end of a container at the end of its closing delimiter and an escaped
+/// character after its backslash, so both are adjusted to where their syntax starts.
+fn event_start(buf: &str, event: &Event<'_>, offset: &Range<usize>) -> usize {
+    match event {
+        Event::End(Tag::Emphasis) => offset.end - 1,
+        Event::End(Tag::Strong) | Event::End(Tag::Strikethrough) => offset.end - 2,
+        Event::Text(text)
+            if offset.start > 0
+                && buf.as_bytes()[offset.start - 1] == b'\\'
+                && text.starts_with(|c: char| c.is_ascii_punctuation()) =>
+        {
+            offset.start - 1
+        }
+        _ => offset.start,
+    }
+}
+
+/// The set of slugs which are used (or reserved) by the anchor links in a document, which ensures
+/// that every generated anchor link is unique.
+#[derive(Debug)]
+struct Anchors(HashSet<String>);
+
+impl Anchors {
+    fn new() -> Self {
+        Anchors(HashSet::new())
+    }
+
+    /// Reserves the ids and names given to the HTML anchor elements (such as
+    /// `<a name="install"></a>`) in a raw HTML block or inline HTML, so that no anchor will be
+    /// generated for them.
+    fn reserve_html_anchors(&mut self, html: &str) {
+        for caps in HTML_ANCHOR_RE.captures_iter(html) {
+            if let Some(id) = attribute_value(&HTML_ANCHOR_ID_RE, &caps[1]) {
+                if !id.is_empty() {
+                    self.0.insert(id.to_string());
+                }
+            }
+        }
+    }
+
+    /// Returns the anchor link for an explicit id, which is used verbatim.
+    fn explicit_anchor(&mut self, id: &str) -> String {
+        self.0.insert(id.to_string());
+        format!("#{}", id)
+    }
+
+    /// Returns a unique anchor link (i.e. a slug prefixed with `#`) for the given slug, using the
+    /// `Slugger`'s candidates if the slug is already in use.
+    fn unique_anchor(&mut self, slugger: &dyn Slugger, mut candidate: String) -> String {
         if self.0.contains(&candidate) {
             let mut x = 1;
             loop {
-                let new_candidate = format!("{}-{}", &candidate, x);
+                let new_candidate = slugger.deduplicate(&candidate, x);
                 if !self.0.contains(&new_candidate) {
                     candidate = new_candidate;
                     break;
@@ -333,70 +1801,795 @@ impl AnchorSlugger {
                 x += 1;
             }
         }
-        self.0.insert(candidate.clone());
+        let anchor = format!("#{}", candidate);
+        // The slug is moved into the set rather than cloned, as the anchor needs its own
+        // allocation regardless
+        self.0.insert(candidate);
+
+        anchor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Flavor;
+
+    mod headers {
+        use super::*;
+
+        #[test]
+        fn returned_in_order() {
+            let md = "# Alpha\n# Bravo\n# Charlie\n# Delta\n# Echo\n# Foxtrot";
+            let mut iter = headers(md).map(Header::into_title);
+
+            assert_eq!(Some("Alpha"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("Bravo"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("Charlie"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("Delta"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("Echo"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("Foxtrot"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn unique_slugs_are_unique() {
+            let md = "# Alpha\n# Bravo\n# Charlie\n# Delta\n# Echo\n# Foxtrot";
+            let mut iter = headers(md).map(Header::into_anchor);
+
+            assert_eq!(Some("#alpha"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#bravo"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#charlie"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#delta"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#echo"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#foxtrot"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn duplicate_slugs_are_uniqued() {
+            let md = "# Alpha\n# Bravo\n## Alpha\n# Delta\n# Alpha\n###### Alpha";
+            let mut iter = headers(md).map(Header::into_anchor);
+
+            assert_eq!(Some("#alpha"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#bravo"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#alpha-1"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#delta"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#alpha-2"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#alpha-3"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn ranges_cover_raw_text() {
+            let md = "# Alpha\n\nSome text.\n\n## Bravo Charlie ##\n### Delta\n";
+            let mut iter = headers(md).map(|h| &md[h.range().unwrap()]);
+
+            assert_eq!(Some("Alpha"), iter.next());
+            assert_eq!(Some("Bravo Charlie ##"), iter.next());
+            assert_eq!(Some("Delta"), iter.next());
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn ranges_exclude_crlf_line_endings() {
+            let md = "# Alpha\r\n## Bravo\r\n";
+            let mut iter = headers(md).map(|h| h.range().unwrap());
+
+            assert_eq!(Some(2..7), iter.next());
+            assert_eq!(Some(12..17), iter.next());
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn empty_header_range_is_empty() {
+            let md = "# Alpha\n##\n# Charlie";
+            let header = headers(md).nth(1).unwrap();
+
+            assert_eq!(Some(10..10), header.range());
+            assert_eq!(Some(2), header.line());
+        }
+
+        #[test]
+        fn empty_headers_after_first_line() {
+            let md = "# Alpha\n##\n### Bravo\n##\r\n# Charlie";
+            let lines = headers(md)
+                .map(|h| (h.title().to_string(), h.line().unwrap()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                vec![
+                    ("Alpha".to_string(), 1),
+                    ("".to_string(), 2),
+                    ("Bravo".to_string(), 3),
+                    ("".to_string(), 4),
+                    ("Charlie".to_string(), 5)
+                ],
+                lines
+            );
+        }
+
+        #[test]
+        fn line_cursor_rescans_behind_offsets() {
+            let md = "a\nb\nc";
+            let mut cursor = LineCursor::new();
+
+            assert_eq!((3, 1), cursor.advance(md, 4));
+            assert_eq!((2, 1), cursor.advance(md, 2));
+        }
+
+        #[test]
+        fn lines_and_columns() {
+            let md = "# Alpha\n\n  ## Bravo\n\n```\n# Not a header\n```\n\n> ### Charlie\n";
+            let mut iter = headers(md).map(|h| (h.line().unwrap(), h.column().unwrap()));
+
+            assert_eq!(Some((1, 3)), iter.next());
+            assert_eq!(Some((3, 6)), iter.next());
+            assert_eq!(Some((9, 7)), iter.next());
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn section_ranges() {
+            let md = "# A\nIntro.\n## B\nBody.\n### C\n#### D\n## E\nEnd.\n";
+            let sections = headers(md)
+                .map(|h| {
+                    (
+                        &md[h.section_range().unwrap()],
+                        &md[h.shallow_section_range().unwrap()],
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                vec![
+                    (md, "# A\nIntro.\n"),
+                    ("## B\nBody.\n### C\n#### D\n", "## B\nBody.\n"),
+                    ("### C\n#### D\n", "### C\n"),
+                    ("#### D\n", "#### D\n"),
+                    ("## E\nEnd.\n", "## E\nEnd.\n"),
+                ],
+                sections
+            );
+        }
+
+        #[test]
+        fn section_ranges_with_level_gaps() {
+            let md = "### A\n# B\n### C\n## D\n";
+            let sections = headers(md)
+                .map(|h| &md[h.section_range().unwrap()])
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                vec!["### A\n", "# B\n### C\n## D\n", "### C\n", "## D\n"],
+                sections
+            );
+        }
+
+        #[test]
+        fn section_ranges_with_setext_headers() {
+            let md = "Title\n=====\n\nText.\n\nSub\n---\n";
+            let sections = headers(md)
+                .map(|h| &md[h.section_range().unwrap()])
+                .collect::<Vec<_>>();
+
+            assert_eq!(vec![md, "Sub\n---\n"], sections);
+        }
+
+        #[test]
+        fn titles_borrow_from_source_when_unchanged() {
+            let md = "# Plain Title
+# <b>Bold</b> Title
+#   Spaced   Out   Title
+";
+            let mut iter = headers(md);
+
+            let header = iter.next().unwrap();
+            assert!(match header.title {
+                Cow::Borrowed(title) => title.as_ptr() == md[2..].as_ptr(),
+                Cow::Owned(_) => false,
+            });
+            assert!(match iter.next().unwrap().title {
+                Cow::Owned(title) => title == "Bold Title",
+                Cow::Borrowed(_) => false,
+            });
+            assert!(match iter.next().unwrap().title {
+                Cow::Owned(title) => title == "Spaced Out Title",
+                Cow::Borrowed(_) => false,
+            });
+        }
+
+        #[test]
+        fn titles_reduce_links_and_images() {
+            let md = "# See [the spec](https://spec.example)
+# [Reference][ref] and [collapsed][] links
+# Visit <https://example.com> or <me@example.com>
+# [![Build Status](https://ci.example/badge.svg)](https://ci.example) Project
+# On <sub>[Go to *Top*](#top)</sub>
+# An [](#empty) link
+
+[ref]: https://ref.example
+[collapsed]: https://collapsed.example
+";
+            let mut iter = headers(md).map(Header::into_title);
+
+            assert_eq!(
+                Some("See the spec"),
+                iter.next().as_ref().map(String::as_str)
+            );
+            assert_eq!(
+                Some("Reference and collapsed links"),
+                iter.next().as_ref().map(String::as_str)
+            );
+            assert_eq!(
+                Some("Visit https://example.com or me@example.com"),
+                iter.next().as_ref().map(String::as_str)
+            );
+            assert_eq!(
+                Some("Build Status Project"),
+                iter.next().as_ref().map(String::as_str)
+            );
+            assert_eq!(
+                Some("On Go to *Top*"),
+                iter.next().as_ref().map(String::as_str)
+            );
+            assert_eq!(Some("An link"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(None, iter.next());
+        }
+
+        #[test]
+        fn titles_keep_markup_as_written() {
+            let md = r"# 5 * 3
+# a_b _c_
+# C:\Windows
+# \*Not\* __strong__ `code` ##
+# [Link](#link)\*star\* [*a*](#a)*b*
+# Tagged [Link](#link) {#tagged}
+";
+            let titles = headers(md).map(Header::into_title).collect::<Vec<_>>();
+
+            assert_eq!(
+                vec![
+                    r"5 * 3",
+                    r"a_b _c_",
+                    r"C:\Windows",
+                    r"\*Not\* __strong__ `code`",
+                    r"Link\*star\* *a**b*",
+                    r"Tagged Link",
+                ],
+                titles
+            );
+        }
+
+        #[test]
+        fn titles_decode_character_references() {
+            let md = "# Tom &amp; Jerry\n# 5 &lt; 6\n# Caf&#xE9; &#42;Menu&#42;";
+            let headers = headers(md).collect::<Vec<_>>();
+            let titles = headers.iter().map(Header::title).collect::<Vec<_>>();
+            let anchors = headers.iter().map(Header::anchor).collect::<Vec<_>>();
+
+            assert_eq!(vec!["Tom & Jerry", "5 < 6", "Café &#42;Menu&#42;"], titles);
+            assert_eq!(vec!["#tom--jerry", "#5--6", "#café-menu"], anchors);
+        }
+
+        #[test]
+        fn titles_drop_footnote_references() {
+            let md = "# Results[^1]\n# Notes [^a] [^b]\n\n[^1]: One.\n[^a]: A.\n[^b]: B.\n";
+            // As with HTML comments, the spaces before a footnote reference are kept in the slug
+            let headers = headers_with_options(md, ParserOptions::gfm()).collect::<Vec<_>>();
+            let titles = headers.iter().map(Header::title).collect::<Vec<_>>();
+            let anchors = headers.iter().map(Header::anchor).collect::<Vec<_>>();
+
+            assert_eq!(vec!["Results", "Notes"], titles);
+            assert_eq!(vec!["#results", "#notes--"], anchors);
+            assert_eq!("[Results](#results)", headers[0].to_string());
+        }
+
+        #[test]
+        fn titles_drop_html_comments() {
+            let md = "# API <!-- draft -->\n# Usage <!-- a > b --> Guide\n";
+            let headers = headers(md).collect::<Vec<_>>();
+            let titles = headers.iter().map(Header::title).collect::<Vec<_>>();
+            let anchors = headers.iter().map(Header::anchor).collect::<Vec<_>>();
+
+            assert_eq!(vec!["API", "Usage Guide"], titles);
+            // As on GitHub, the space before a trailing comment leaves a trailing dash
+            assert_eq!(vec!["#api-", "#usage--guide"], anchors);
+        }
+
+        #[test]
+        fn explicit_ids() {
+            let md = "# Intro {#custom-id}
+## Setup {: #kramdown .class}
+## Plain {.unnumbered}
+## Braces {not attributes}
+## Code `{#not-an-id}`
+";
+            let headers = headers(md).collect::<Vec<_>>();
+            let titles = headers.iter().map(Header::title).collect::<Vec<_>>();
+            let anchors = headers.iter().map(Header::anchor).collect::<Vec<_>>();
+
+            assert_eq!(
+                vec![
+                    "Intro",
+                    "Setup",
+                    "Plain",
+                    "Braces {not attributes}",
+                    "Code `{#not-an-id}`"
+                ],
+                titles
+            );
+            assert_eq!(
+                vec![
+                    "#custom-id",
+                    "#kramdown",
+                    "#plain",
+                    "#braces-not-attributes",
+                    "#code-not-an-id"
+                ],
+                anchors
+            );
+            assert_eq!("Intro {#custom-id}", headers[0].raw_title());
+            assert_eq!(&[Inline::Text("Intro".into())], headers[0].inlines());
+        }
+
+        #[test]
+        fn explicit_ids_are_reserved() {
+            let md = "# Install\n# Setup {#install}\n# Install\n# Other {#install-2}";
+            let mut iter = headers(md).map(Header::into_anchor);
+
+            assert_eq!(Some("#install-1"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#install"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#install-3"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#install-2"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(None, iter.next());
+        }
 
-        self.0.get(&candidate).unwrap()
-    }
-}
+        #[test]
+        fn explicit_id_inlines_keep_other_content() {
+            let header = headers("# The `main` fn {#main}").next().unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+            assert_eq!(
+                &[
+                    Inline::Text("The ".into()),
+                    Inline::Code("main".into()),
+                    Inline::Text(" fn".into()),
+                ],
+                header.inlines()
+            );
+            assert_eq!("#main", header.anchor());
+        }
 
-    mod headers {
-        use super::*;
+        #[test]
+        fn omitted_headers() {
+            let md = "# Alpha
+## Contents <!-- omit in toc -->
+## Bravo <!-- Omit From TOC -->
+## Charlie
+";
+            let mut iter = headers(md).map(Header::into_title);
+
+            assert_eq!(Some("Alpha"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("Charlie"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(None, iter.next());
+        }
 
         #[test]
-        fn returned_in_order() {
-            let md = "# Alpha\n# Bravo\n# Charlie\n# Delta\n# Echo\n# Foxtrot";
+        fn omitted_subtrees() {
+            let md = "# Alpha
+## Legal <!-- omit subtree in toc -->
+### Terms
+#### Detail
+## Bravo
+### Charlie <!-- omit subtree from toc -->
+# Delta
+";
             let mut iter = headers(md).map(Header::into_title);
 
             assert_eq!(Some("Alpha"), iter.next().as_ref().map(String::as_str));
             assert_eq!(Some("Bravo"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("Charlie"), iter.next().as_ref().map(String::as_str));
             assert_eq!(Some("Delta"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("Echo"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("Foxtrot"), iter.next().as_ref().map(String::as_str));
             assert_eq!(None, iter.next());
         }
 
         #[test]
-        fn unique_slugs_are_unique() {
-            let md = "# Alpha\n# Bravo\n# Charlie\n# Delta\n# Echo\n# Foxtrot";
+        fn omitted_headers_reserve_slugs() {
+            // As on GitHub, the space before the annotation leaves a trailing dash, so an omitted
+            // `Intro` heading reserves `intro-` and a plain `Intro` heading still gets `intro`
+            let md = "# Intro <!-- omit in toc -->\n# Intro <!-- draft -->\n# Intro";
             let mut iter = headers(md).map(Header::into_anchor);
 
-            assert_eq!(Some("#alpha"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#bravo"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#charlie"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#delta"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#echo"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#foxtrot"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#intro--1"), iter.next().as_ref().map(String::as_str));
+            assert_eq!(Some("#intro"), iter.next().as_ref().map(String::as_str));
             assert_eq!(None, iter.next());
         }
 
         #[test]
-        fn duplicate_slugs_are_uniqued() {
-            let md = "# Alpha\n# Bravo\n## Alpha\n# Delta\n# Alpha\n###### Alpha";
-            let mut iter = headers(md).map(Header::into_anchor);
+        fn omitted_headers_end_sections() {
+            let md = "# A\nIntro.\n## B <!-- omit in toc -->\nBody.\n";
+            let header = headers(md).next().unwrap();
 
-            assert_eq!(Some("#alpha"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#bravo"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#alpha-1"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#delta"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#alpha-2"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(Some("#alpha-3"), iter.next().as_ref().map(String::as_str));
-            assert_eq!(None, iter.next());
+            assert_eq!(Some(md), header.section_range().map(|r| &md[r]));
+            assert_eq!(
+                Some("# A\nIntro.\n"),
+                header.shallow_section_range().map(|r| &md[r])
+            );
+        }
+
+        #[test]
+        fn html_headings_are_opt_in() {
+            let md = "<h2 id=\"usage\">Usage</h2>\n";
+
+            assert_eq!(0, headers(md).count());
+            assert_eq!(0, headers(md).html_headings(false).count());
+            assert_eq!(1, headers(md).html_headings(true).count());
+        }
+
+        #[test]
+        fn html_headings() {
+            let md = "<h1 align=\"center\">My Project</h1>
+
+## Install
+
+<h2 class=\"x\" id='usage'>
+  Usage <code>mtoc</code>
+</h2>
+
+### Options
+";
+            let headers = headers(md).html_headings(true).collect::<Vec<_>>();
+            let levels = headers.iter().map(Header::level).collect::<Vec<_>>();
+            let titles = headers.iter().map(Header::title).collect::<Vec<_>>();
+            let anchors = headers.iter().map(Header::anchor).collect::<Vec<_>>();
+            let lines = headers.iter().map(Header::line).collect::<Vec<_>>();
+
+            assert_eq!(vec![1, 2, 2, 3], levels);
+            assert_eq!(
+                vec!["My Project", "Install", "Usage mtoc", "Options"],
+                titles
+            );
+            assert_eq!(
+                vec!["#my-project", "#install", "#usage", "#options"],
+                anchors
+            );
+            assert_eq!(vec![Some(1), Some(3), Some(5), Some(9)], lines);
+            assert_eq!("My Project", &md[headers[0].range().unwrap()]);
+            assert_eq!(
+                "<h2 class=\"x\" id='usage'>\n  Usage <code>mtoc</code>\n</h2>\n\n",
+                &md[headers[2].shallow_section_range().unwrap()]
+            );
+        }
+
+        #[test]
+        fn html_headings_in_one_block() {
+            let md = "<div align=\"center\">\n<h1>One</h1><h2>Two</h2>\n</div>\n\n# Three\n";
+            let titles = headers(md)
+                .html_headings(true)
+                .map(Header::into_title)
+                .collect::<Vec<_>>();
+
+            assert_eq!(vec!["One", "Two", "Three"], titles);
+        }
+
+        #[test]
+        fn html_headings_must_be_closed() {
+            let md = "<h2>Unclosed\n\n<h2>Mismatched</h3>\n\n# Closed\n";
+            let titles = headers(md)
+                .html_headings(true)
+                .map(Header::into_title)
+                .collect::<Vec<_>>();
+
+            assert_eq!(vec!["Closed"], titles);
+        }
+
+        #[test]
+        fn anchor_element_ids_are_reserved() {
+            let md = "<a name=\"install\"></a>
+
+# Install
+
+See <a id='usage'>usage</a>.
+
+# Usage
+";
+            let anchors = headers(md).map(Header::into_anchor).collect::<Vec<_>>();
+
+            assert_eq!(vec!["#install-1", "#usage-1"], anchors);
+        }
+
+        #[test]
+        fn ids_later_in_the_source_are_reserved() {
+            let md = "# Install
+# Usage
+# Setup {#install}
+
+See <a name=\"usage\"></a>below.
+";
+            let anchors = headers(md).map(Header::into_anchor).collect::<Vec<_>>();
+
+            assert_eq!(vec!["#install-1", "#usage-1", "#install"], anchors);
+        }
+
+        #[test]
+        fn embedded_anchor_elements() {
+            let md = "## <a name=\"install\"></a>Install
+## **<a id=install-it></a>Install** again
+## <a href=\"#install\">Install</a>
+## <a name=\"setup\"></a>Setup {#custom}
+";
+            let headers = headers(md).collect::<Vec<_>>();
+            let titles = headers.iter().map(Header::title).collect::<Vec<_>>();
+            let anchors = headers.iter().map(Header::anchor).collect::<Vec<_>>();
+
+            assert_eq!(
+                vec!["Install", "**Install** again", "Install", "Setup"],
+                titles
+            );
+            assert_eq!(
+                vec!["#install", "#install-it", "#install-1", "#custom"],
+                anchors
+            );
+        }
+
+        #[test]
+        fn embedded_anchor_elements_in_html_headings() {
+            let md = "<h2><a name=\"usage\"></a>Usage</h2>\n";
+            let anchors = headers(md)
+                .html_headings(true)
+                .map(Header::into_anchor)
+                .collect::<Vec<_>>();
+
+            assert_eq!(vec!["#usage"], anchors);
+        }
+
+        #[test]
+        fn html_heading_ids_are_reserved() {
+            let md = "## Usage\n\n<h2 id=\"usage\">How to Use</h2>\n";
+            let anchors = headers(md)
+                .html_headings(true)
+                .map(Header::into_anchor)
+                .collect::<Vec<_>>();
+
+            assert_eq!(vec!["#usage-1", "#usage"], anchors);
+        }
+
+        #[test]
+        fn custom_slugger() {
+            struct Lowercase;
+
+            impl Slugger for Lowercase {
+                fn slugify(&self, text: &str) -> String {
+                    text.to_lowercase()
+                }
+
+                fn deduplicate(&self, slug: &str, n: usize) -> String {
+                    format!("{}_{}", slug, n)
+                }
+            }
+
+            let md = "# Intro\n# Other {#intro_1}\n# Intro\n# Cafe\u{301}\n";
+            let anchors = headers_with_slugger(md, ParserOptions::default(), Lowercase)
+                .map(Header::into_anchor)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                vec!["#intro", "#intro_1", "#intro_2", "#caf\u{E9}"],
+                anchors
+            );
+        }
+
+        #[test]
+        fn gitlab_flavor() {
+            let md = "# Step 1 -- Install\n# Step 1 -- Install\n# 2019\n# :tada: Done!\n";
+            let anchors = headers_with_slugger(md, ParserOptions::default(), Flavor::GitLab)
+                .map(Header::into_anchor)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                vec![
+                    "#step-1-install",
+                    "#step-1-install-1",
+                    "#anchor-2019",
+                    "#tada-done"
+                ],
+                anchors
+            );
+        }
+
+        #[test]
+        fn pandoc_flavor() {
+            let md = "# 1. Intro\n# Intro\n# 2019\n# 2020\n# Section\n";
+            let anchors = headers_with_slugger(md, ParserOptions::default(), Flavor::Pandoc)
+                .map(Header::into_anchor)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                vec!["#intro", "#intro-1", "#section", "#section-1", "#section-2"],
+                anchors
+            );
+        }
+
+        #[test]
+        fn kramdown_flavor() {
+            let md = "# Hallo\n# Not now\n# Hallo\n# 23\n# Überblick\n# 日本語\n";
+            let anchors = headers_with_slugger(md, ParserOptions::default(), Flavor::Kramdown)
+                .map(Header::into_anchor)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                vec![
+                    "#hallo",
+                    "#not-now",
+                    "#hallo-1",
+                    "#section",
+                    "#berblick",
+                    "#section-1"
+                ],
+                anchors
+            );
+        }
+
+        #[test]
+        fn containers_are_included_by_default() {
+            let md = "# A\n> # B\n- # C\n<details>\n\n# D\n\n</details>\n";
+            let titles = headers(md).map(Header::into_title).collect::<Vec<_>>();
+
+            assert_eq!(vec!["A", "B", "C", "D"], titles);
+        }
+
+        #[test]
+        fn excluded_containers() {
+            let md = "# Alpha
+> # Quoted
+>
+> - # Quoted List
+
+1. Item
+   # Listed
+
+<details>
+<summary>More</summary>
+
+# Collapsed
+
+</details>
+
+# Bravo
+";
+            let titles = |containers: &[Container]| {
+                containers
+                    .iter()
+                    .fold(headers(md), |headers, &container| {
+                        headers.exclude(container)
+                    })
+                    .map(Header::into_title)
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                vec!["Alpha", "Listed", "Collapsed", "Bravo"],
+                titles(&[Container::BlockQuote])
+            );
+            assert_eq!(
+                vec!["Alpha", "Quoted", "Collapsed", "Bravo"],
+                titles(&[Container::List])
+            );
+            assert_eq!(
+                vec!["Alpha", "Quoted", "Quoted List", "Listed", "Bravo"],
+                titles(&[Container::Details])
+            );
+            assert_eq!(
+                vec!["Alpha", "Bravo"],
+                titles(&[Container::BlockQuote, Container::List, Container::Details])
+            );
+        }
+
+        #[test]
+        fn excluded_headers_reserve_slugs() {
+            let md = "# Usage\n> # Usage\n# Usage\n";
+            let anchors = headers(md)
+                .exclude(Container::BlockQuote)
+                .map(Header::into_anchor)
+                .collect::<Vec<_>>();
+
+            assert_eq!(vec!["#usage", "#usage-2"], anchors);
+        }
+
+        #[test]
+        fn excluded_headers_do_not_end_sections() {
+            let md = "## Usage\nText.\n> # Quoted\n## Help\n";
+            let header = headers(md).exclude(Container::BlockQuote).next().unwrap();
+
+            assert_eq!(
+                "## Usage\nText.\n> # Quoted\n",
+                &md[header.section_range().unwrap()]
+            );
+            assert_eq!(
+                "## Usage\nText.\n> # Quoted\n",
+                &md[header.shallow_section_range().unwrap()]
+            );
+        }
+
+        #[test]
+        fn front_matter_is_skipped() {
+            let md = "---\ntitle: Usage\n---\n# Body\n\n## Detail\n";
+            let headers = headers(md).collect::<Vec<_>>();
+            let titles = headers.iter().map(Header::title).collect::<Vec<_>>();
+            let lines = headers.iter().map(Header::line).collect::<Vec<_>>();
+
+            assert_eq!(vec!["Body", "Detail"], titles);
+            assert_eq!(vec![Some(4), Some(6)], lines);
+            assert_eq!(
+                "# Body\n\n## Detail\n",
+                &md[headers[0].section_range().unwrap()]
+            );
+        }
+
+        #[test]
+        fn toml_front_matter_is_skipped() {
+            let md = "+++\ntitle = \"Usage\"\n+++\n\n## Detail\n";
+            let titles = headers(md).map(Header::into_title).collect::<Vec<_>>();
+
+            assert_eq!(vec!["Detail"], titles);
+        }
+
+        #[test]
+        fn thematic_break_is_not_front_matter() {
+            let md = "***\nNot Front Matter\n---\n";
+            let titles = headers(md).map(Header::into_title).collect::<Vec<_>>();
+
+            assert_eq!(vec!["Not Front Matter"], titles);
+        }
+
+        #[test]
+        fn setext_ranges_exclude_underlines() {
+            let md = "***\nNot Front Matter\n---\n\nTwo\r\nLines *x*\r\n===\r\n";
+            let headers = headers(md).collect::<Vec<_>>();
+            let raw_titles = headers.iter().map(Header::raw_title).collect::<Vec<_>>();
+
+            assert_eq!(vec!["Not Front Matter", "Two\r\nLines *x*"], raw_titles);
+            assert_eq!(Some(4..20), headers[0].range());
+            assert_eq!("Two Lines *x*", headers[1].title());
+        }
+
+        #[test]
+        fn raw_titles_are_verbatim() {
+            let md = "# See [the spec](https://spec.example)\n## <b>Bold</b>  Title";
+            let mut iter = headers(md);
+
+            let header = iter.next().unwrap();
+            assert_eq!("See [the spec](https://spec.example)", header.raw_title());
+            assert_eq!("[See the spec](#see-the-spec)", header.to_string());
+            assert_eq!("<b>Bold</b>  Title", iter.next().unwrap().raw_title());
+        }
+
+        #[test]
+        fn columns_count_characters() {
+            let md = "> 日本語\n> # Русский\n";
+            let header = headers(md).next().unwrap();
+
+            assert_eq!(Some(2), header.line());
+            assert_eq!(Some(5), header.column());
         }
     }
 
     mod header {
         use super::*;
 
-        fn header() -> Header {
+        fn header() -> Header<'static> {
             Header {
                 level: 3,
-                title: "A Title to Remember".to_string(),
-                anchor: "#a-title-to-remember".to_string(),
+                title: "A Title to Remember".into(),
+                raw_title: "A Title to Remember".into(),
+                anchor: "#a-title-to-remember".into(),
+                inlines: vec![Inline::Text("A Title to Remember".into())],
+                source: Some(Source {
+                    range: 14..33,
+                    line: 2,
+                    column: 5,
+                    section_range: 10..80,
+                    shallow_section_range: 10..55,
+                }),
             }
         }
 
@@ -415,6 +2608,31 @@ mod tests {
             assert_eq!("#a-title-to-remember", header().anchor());
         }
 
+        #[test]
+        fn range() {
+            assert_eq!(Some(14..33), header().range());
+        }
+
+        #[test]
+        fn line() {
+            assert_eq!(Some(2), header().line());
+        }
+
+        #[test]
+        fn column() {
+            assert_eq!(Some(5), header().column());
+        }
+
+        #[test]
+        fn section_range() {
+            assert_eq!(Some(10..80), header().section_range());
+        }
+
+        #[test]
+        fn shallow_section_range() {
+            assert_eq!(Some(10..55), header().shallow_section_range());
+        }
+
         #[test]
         fn into_title() {
             assert_eq!("A Title to Remember", header().into_title());
@@ -445,6 +2663,62 @@ mod tests {
             assert_eq!(6, header().demote().demote().demote().demote().level());
         }
 
+        #[test]
+        fn with_level() {
+            assert_eq!(5, header().with_level(5).unwrap().level());
+        }
+
+        #[test]
+        fn with_level_out_of_range() {
+            assert_eq!(0, header().with_level(0).unwrap_err().level());
+            assert_eq!(7, header().with_level(7).unwrap_err().level());
+        }
+
+        #[test]
+        fn raw_title() {
+            assert_eq!("A Title to Remember", header().raw_title());
+        }
+
+        #[test]
+        fn with_title() {
+            let header = header().with_title("Another Title");
+
+            assert_eq!("Another Title", header.title());
+            assert_eq!("Another Title", header.raw_title());
+            assert_eq!("#a-title-to-remember", header.anchor());
+            assert_eq!(&[Inline::Text("Another Title".into())], header.inlines());
+        }
+
+        #[test]
+        fn with_anchor() {
+            let header = header().with_anchor("#remember");
+
+            assert_eq!("A Title to Remember", header.title());
+            assert_eq!("#remember", header.anchor());
+        }
+
+        #[test]
+        fn with_transforms_keep_source() {
+            let header = header()
+                .with_level(1)
+                .unwrap()
+                .with_title("Other")
+                .with_anchor("#other");
+
+            assert_eq!(Some(14..33), header.range());
+        }
+
+        #[test]
+        fn into_owned() {
+            let md = String::from("# A Title\nText.\n");
+            let header = headers(&md).next().unwrap().into_owned();
+            drop(md);
+
+            assert_eq!("A Title", header.title());
+            assert_eq!("#a-title", header.anchor());
+            assert_eq!(Some(2..9), header.range());
+        }
+
         #[test]
         fn display() {
             assert_eq!(
@@ -452,5 +2726,111 @@ mod tests {
                 header().to_string()
             )
         }
+
+        #[test]
+        fn display_escapes_title() {
+            assert_eq!(
+                r"[Array\[T\] \| Slice](#a-title-to-remember)",
+                header().with_title("Array[T] | Slice").to_string()
+            )
+        }
+    }
+    mod header_builder {
+        use super::*;
+
+        #[test]
+        fn new() {
+            let header = Header::new(3, "A Title to Remember").unwrap();
+
+            assert_eq!(3, header.level());
+            assert_eq!("A Title to Remember", header.title());
+            assert_eq!("#a-title-to-remember", header.anchor());
+        }
+
+        #[test]
+        fn new_normalizes_anchor() {
+            let header = Header::new(1, "<b>Bold</b> & Brave!").unwrap();
+
+            assert_eq!("#bold--brave", header.anchor());
+        }
+
+        #[test]
+        fn new_has_no_source() {
+            let header = Header::new(1, "Nowhere").unwrap();
+
+            assert_eq!(None, header.range());
+            assert_eq!(None, header.line());
+            assert_eq!(None, header.column());
+            assert_eq!(None, header.section_range());
+            assert_eq!(None, header.shallow_section_range());
+        }
+
+        #[test]
+        fn new_has_text_inlines() {
+            let header = Header::new(1, "Plain *Text*").unwrap();
+
+            assert_eq!(&[Inline::Text("Plain *Text*".into())], header.inlines());
+            assert!(Header::new(1, "").unwrap().inlines().is_empty());
+        }
+
+        #[test]
+        fn new_with_invalid_levels() {
+            assert_eq!(LevelError { level: 0 }, Header::new(0, "Zero").unwrap_err());
+            assert_eq!(
+                LevelError { level: 7 },
+                Header::new(7, "Seven").unwrap_err()
+            );
+        }
+
+        #[test]
+        fn defaults() {
+            let header = HeaderBuilder::new("Default").build().unwrap();
+
+            assert_eq!(1, header.level());
+            assert_eq!("Default", header.title());
+            assert_eq!("#default", header.anchor());
+        }
+
+        #[test]
+        fn explicit_anchor() {
+            let header = HeaderBuilder::new("Explicit")
+                .level(6)
+                .anchor("#custom")
+                .build()
+                .unwrap();
+
+            assert_eq!(6, header.level());
+            assert_eq!("#custom", header.anchor());
+        }
+
+        #[test]
+        fn slugger() {
+            let header = HeaderBuilder::new("Café Menu")
+                .slugger(Flavor::Bitbucket)
+                .build()
+                .unwrap();
+
+            assert_eq!("Café Menu", header.title());
+            assert_eq!("#markdown-header-cafe-menu", header.anchor());
+
+            let header = HeaderBuilder::new("Café Menu")
+                .slugger(Flavor::Bitbucket)
+                .anchor("#menu")
+                .build()
+                .unwrap();
+
+            assert_eq!("#menu", header.anchor());
+        }
+
+        #[test]
+        fn invalid_level() {
+            let err = HeaderBuilder::new("Deep").level(42).build().unwrap_err();
+
+            assert_eq!(42, err.level());
+            assert_eq!(
+                "header level must be between 1 and 6, got 42",
+                err.to_string()
+            );
+        }
     }
 }
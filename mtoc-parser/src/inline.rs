@@ -0,0 +1,384 @@
+// Copyright 2019 Fletcher Nichol and/or applicable contributors.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license (see <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
+// distributed except according to those terms.
+
+use pulldown_cmark::{CowStr, Event, Tag};
+use std::borrow::Cow;
+
+/// An element of a heading's inline content.
+///
+/// The inline content of a [`Header`] is available via its [`inlines`] method and preserves the
+/// Markdown structure of the heading, such as code spans, emphasis, and links. This allows a
+/// [`Format`] implementation to render a title differently (for example, keeping code spans but
+/// dropping emphasis) without re-parsing the normalized title.
+///
+/// # Examples
+///
+/// ```rust
+/// use mtoc_parser::{headers, Inline};
+///
+/// let header = headers("# Using `cargo` *quickly*").next().unwrap();
+///
+/// assert_eq!(
+///     &[
+///         Inline::Text("Using ".into()),
+///         Inline::Code("cargo".into()),
+///         Inline::Text(" ".into()),
+///         Inline::Emphasis(vec![Inline::Text("quickly".into())]),
+///     ],
+///     header.inlines()
+/// );
+/// ```
+///
+/// [`Format`]: trait.Format.html
+/// [`Header`]: struct.Header.html
+/// [`inlines`]: struct.Header.html#method.inlines
+#[derive(Clone, Debug, PartialEq)]
+pub enum Inline<'a> {
+    /// Plain text.
+    Text(Cow<'a, str>),
+    /// A code span, holding its contents without the surrounding backticks.
+    Code(Cow<'a, str>),
+    /// Emphasized content, such as `*text*` or `_text_`.
+    Emphasis(Vec<Inline<'a>>),
+    /// Strongly emphasized content, such as `**text**` or `__text__`.
+    Strong(Vec<Inline<'a>>),
+    /// Struck through content, such as `~~text~~`.
+    Strikethrough(Vec<Inline<'a>>),
+    /// A link.
+    Link {
+        /// The destination URL of the link.
+        url: Cow<'a, str>,
+        /// The title of the link, which is empty if none was provided.
+        title: Cow<'a, str>,
+        /// The content of the link.
+        content: Vec<Inline<'a>>,
+    },
+    /// An image.
+    Image {
+        /// The source URL of the image.
+        url: Cow<'a, str>,
+        /// The title of the image, which is empty if none was provided.
+        title: Cow<'a, str>,
+        /// The alternative text of the image.
+        alt: Vec<Inline<'a>>,
+    },
+    /// Inline HTML, such as a single `<b>` tag.
+    Html(Cow<'a, str>),
+    /// A soft line break, as found in a multi-line setext heading.
+    SoftBreak,
+    /// A hard line break.
+    HardBreak,
+}
+
+impl<'a> Inline<'a> {
+    /// Consumes this `Inline`, returning a new `Inline` which owns all of its content.
+    pub fn into_owned(self) -> Inline<'static> {
+        match self {
+            Inline::Text(text) => Inline::Text(Cow::Owned(text.into_owned())),
+            Inline::Code(code) => Inline::Code(Cow::Owned(code.into_owned())),
+            Inline::Emphasis(inlines) => Inline::Emphasis(into_owned(inlines)),
+            Inline::Strong(inlines) => Inline::Strong(into_owned(inlines)),
+            Inline::Strikethrough(inlines) => Inline::Strikethrough(into_owned(inlines)),
+            Inline::Link {
+                url,
+                title,
+                content,
+            } => Inline::Link {
+                url: Cow::Owned(url.into_owned()),
+                title: Cow::Owned(title.into_owned()),
+                content: into_owned(content),
+            },
+            Inline::Image { url, title, alt } => Inline::Image {
+                url: Cow::Owned(url.into_owned()),
+                title: Cow::Owned(title.into_owned()),
+                alt: into_owned(alt),
+            },
+            Inline::Html(html) => Inline::Html(Cow::Owned(html.into_owned())),
+            Inline::SoftBreak => Inline::SoftBreak,
+            Inline::HardBreak => Inline::HardBreak,
+        }
+    }
+}
+
+pub(crate) fn into_owned(inlines: Vec<Inline<'_>>) -> Vec<Inline<'static>> {
+    inlines.into_iter().map(Inline::into_owned).collect()
+}
+
+/// Returns the rendered text content of the given inline content.
+///
+/// Only text and code spans contribute to the text content, so emphasis markers, inline HTML and
+/// images are dropped while the content of any containers (such as emphasis or links) is kept.
+pub(crate) fn text_content(inlines: &[Inline<'_>]) -> String {
+    let mut text = String::new();
+    push_text_content(&mut text, inlines);
+    text
+}
+
+fn push_text_content(text: &mut String, inlines: &[Inline<'_>]) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(s) | Inline::Code(s) => text.push_str(s),
+            Inline::Emphasis(children)
+            | Inline::Strong(children)
+            | Inline::Strikethrough(children)
+            | Inline::Link {
+                content: children, ..
+            } => push_text_content(text, children),
+            Inline::SoftBreak | Inline::HardBreak => text.push(' '),
+            Inline::Image { .. } | Inline::Html(_) => (),
+        }
+    }
+}
+
+/// Builds a tree of [`Inline`]s from the Markdown events found inside a heading.
+///
+/// [`Inline`]: enum.Inline.html
+#[derive(Debug, Default)]
+pub(crate) struct InlineCollector<'a> {
+    inlines: Vec<Inline<'a>>,
+    stack: Vec<(Tag<'a>, Vec<Inline<'a>>)>,
+}
+
+impl<'a> InlineCollector<'a> {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn push(&mut self, event: Event<'a>) {
+        match event {
+            Event::Start(tag) => self.stack.push((tag, Vec::new())),
+            Event::End(_) => {
+                if let Some((tag, children)) = self.stack.pop() {
+                    let inline = match tag {
+                        Tag::Emphasis => Inline::Emphasis(children),
+                        Tag::Strong => Inline::Strong(children),
+                        Tag::Strikethrough => Inline::Strikethrough(children),
+                        Tag::Link(_, url, title) => Inline::Link {
+                            url: cow(url),
+                            title: cow(title),
+                            content: children,
+                        },
+                        Tag::Image(_, url, title) => Inline::Image {
+                            url: cow(url),
+                            title: cow(title),
+                            alt: children,
+                        },
+                        // Any other container is transparent, so keep its content in place
+                        _ => {
+                            self.current().extend(children);
+                            return;
+                        }
+                    };
+                    self.current().push(inline);
+                }
+            }
+            Event::Text(text) => self.push_text(text),
+            Event::Code(code) => self.current().push(Inline::Code(cow(code))),
+            Event::Html(html) | Event::InlineHtml(html) => {
+                self.current().push(Inline::Html(cow(html)))
+            }
+            Event::SoftBreak => self.current().push(Inline::SoftBreak),
+            Event::HardBreak => self.current().push(Inline::HardBreak),
+            // A footnote reference is not part of the heading's text, so it is dropped from both
+            // the title and the slug
+            Event::FootnoteReference(_) => (),
+            _ => (),
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<Inline<'a>> {
+        // Close any containers which were left open
+        while !self.stack.is_empty() {
+            self.push(Event::End(Tag::Paragraph));
+        }
+        self.inlines
+    }
+
+    /// Pushes a text node, merging it with a directly preceding text node as the parser can split
+    /// a single run of text into several events.
+    fn push_text(&mut self, text: CowStr<'a>) {
+        let current = self.current();
+        match current.last_mut() {
+            Some(Inline::Text(prev)) => prev.to_mut().push_str(&text),
+            _ => current.push(Inline::Text(cow(text))),
+        }
+    }
+
+    fn current(&mut self) -> &mut Vec<Inline<'a>> {
+        match self.stack.last_mut() {
+            Some((_, children)) => children,
+            None => &mut self.inlines,
+        }
+    }
+}
+
+fn cow(s: CowStr<'_>) -> Cow<'_, str> {
+    match s {
+        CowStr::Borrowed(s) => Cow::Borrowed(s),
+        s => Cow::Owned(s.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers;
+
+    fn inlines(md: &str) -> Vec<Inline<'_>> {
+        headers(md).next().unwrap().inlines().to_vec()
+    }
+
+    fn text(s: &str) -> Inline<'_> {
+        Inline::Text(s.into())
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(vec![text("Plain Title")], inlines("# Plain Title"));
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Vec::<Inline>::new(), inlines("#"));
+    }
+
+    #[test]
+    fn code() {
+        assert_eq!(
+            vec![text("The "), Inline::Code("main".into()), text(" function")],
+            inlines("# The `main` function")
+        );
+    }
+
+    #[test]
+    fn emphasis_and_strong() {
+        assert_eq!(
+            vec![
+                text("The "),
+                Inline::Emphasis(vec![text("really")]),
+                text(" "),
+                Inline::Strong(vec![text("important "), Inline::Code("part".into())]),
+            ],
+            inlines("## The *really* __important `part`__")
+        );
+    }
+
+    #[test]
+    fn nested_emphasis() {
+        assert_eq!(
+            vec![Inline::Strong(vec![
+                text("a "),
+                Inline::Emphasis(vec![text("b")]),
+            ])],
+            inlines("# **a _b_**")
+        );
+    }
+
+    #[test]
+    fn link() {
+        assert_eq!(
+            vec![
+                text("On "),
+                Inline::Link {
+                    url: "#top".into(),
+                    title: "Back".into(),
+                    content: vec![text("Go to "), Inline::Emphasis(vec![text("Top")])],
+                },
+            ],
+            inlines("# On [Go to *Top*](#top \"Back\")")
+        );
+    }
+
+    #[test]
+    fn image() {
+        assert_eq!(
+            vec![
+                Inline::Image {
+                    url: "logo.png".into(),
+                    title: "".into(),
+                    alt: vec![text("Logo")],
+                },
+                text(" Project"),
+            ],
+            inlines("# ![Logo](logo.png) Project")
+        );
+    }
+
+    #[test]
+    fn html() {
+        assert_eq!(
+            vec![
+                Inline::Html("<b>".into()),
+                text("Bold"),
+                Inline::Html("</b>".into()),
+            ],
+            inlines("# <b>Bold</b>")
+        );
+    }
+
+    #[test]
+    fn setext_soft_break() {
+        assert_eq!(
+            vec![text("Two"), Inline::SoftBreak, text("Lines")],
+            inlines("Two\nLines\n===\n")
+        );
+    }
+
+    #[test]
+    fn split_text_is_merged() {
+        assert_eq!(
+            vec![text("Fish & Chips [1]")],
+            inlines("# Fish &amp; Chips \\[1]")
+        );
+    }
+
+    #[test]
+    fn footnote_references_are_dropped() {
+        let md = "# Results[^1]\n\n[^1]: One.\n";
+        let inlines = crate::headers_with_options(md, crate::ParserOptions::gfm())
+            .next()
+            .unwrap()
+            .inlines()
+            .to_vec();
+
+        assert_eq!(vec![text("Results")], inlines);
+    }
+
+    #[test]
+    fn text_content_drops_markup() {
+        let inlines = inlines("# The *really* __important__ `part` <b>here</b> ![x](x.png)");
+
+        assert_eq!("The really important part here ", text_content(&inlines));
+    }
+
+    #[test]
+    fn text_content_keeps_link_content() {
+        assert_eq!(
+            "On Go to Top",
+            text_content(&inlines("# On [Go to *Top*](#top)"))
+        );
+    }
+
+    #[test]
+    fn inlines_into_owned() {
+        let md = String::from("# A [*link*](#a)");
+        let owned = into_owned(inlines(&md));
+        drop(md);
+
+        assert_eq!(
+            vec![
+                text("A "),
+                Inline::Link {
+                    url: "#a".into(),
+                    title: "".into(),
+                    content: vec![Inline::Emphasis(vec![text("link")])],
+                },
+            ],
+            owned
+        );
+    }
+}
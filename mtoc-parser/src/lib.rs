@@ -13,18 +13,23 @@
 //!
 //! This library parses a Markdown ([CommonMark]) string slice and generates an `Iterator` of
 //! [`Header`] entries which correspond to the heading structure of the document. Each heading's
-//! level is captured, its title is normalized for Markdown output, and a URL anchor slug is
-//! generated. The title and anchor slug conform to the auto-generated links produced by GitHub
-//! Markdown rendering and Gists. The `Header`s can be consumed, mutated, transformed, filtered
-//! over trivially as they are presented via an `Iterator`. A [`Formatter`] is provided which can
-//! consume `Header`s and output a formatted table of contents to a 'writer' which implements the
-//! `Write` trait. Finally, a [`WriterBuilder`] is provided which combines all of the above (with
-//! reasonable defaults) and writes the table of contents inlined into the source Markdown document
-//! to a provided 'writer'.
+//! level is captured, its title is normalized for Markdown output, its structured [`Inline`]
+//! content is kept, and a URL anchor slug is generated. The title and anchor slug conform to the
+//! auto-generated links produced by GitHub Markdown rendering and Gists. The `Header`s can be
+//! consumed, mutated, transformed, filtered over trivially as they are presented via an `Iterator`,
+//! and additional `Header`s can be built with a [`HeaderBuilder`]. `Header`s can also be collected
+//! into an [`Outline`] tree to navigate or prune the heading hierarchy. A [`Formatter`] is provided
+//! which can consume `Header`s and output a formatted table of contents to a 'writer' which
+//! implements the `Write` trait. Finally, a [`WriterBuilder`] is provided which combines all of the
+//! above (with reasonable defaults) and writes the table of contents inlined into the source
+//! Markdown document to a provided 'writer'.
 //!
 //! [CommonMark]: https://commonmark.org/
 //! [`Formatter`]: enum.Formatter.html
 //! [`Header`]: struct.Header.html
+//! [`HeaderBuilder`]: struct.HeaderBuilder.html
+//! [`Inline`]: enum.Inline.html
+//! [`Outline`]: struct.Outline.html
 //! [`WriterBuilder`]: struct.WriterBuilder.html
 //!
 //! # Usage
@@ -33,7 +38,7 @@
 //!
 //! ```toml
 //! [dependencies]
-//! mtoc-parser = "0.1.0"
+//! mtoc-parser = "0.2.0"
 //! ```
 //!
 //! ## Quick Example
@@ -247,14 +252,30 @@
 //! * [Anchors in Markdown](https://gist.github.com/asabaylus/3071099) Gist with good links,
 //!   discussions and edge conditions
 
-#![doc(html_root_url = "https://docs.rs/mtoc-parser/0.1.0")]
+#![doc(html_root_url = "https://docs.rs/mtoc-parser/0.2.0")]
 #![deny(missing_docs)]
 
+mod emoji;
+mod entity;
 mod format;
+mod front_matter;
 mod header;
+mod inline;
 mod normalize;
+mod options;
+mod outline;
+mod slug;
 mod write;
 
 pub use format::{Format, Formatter};
-pub use header::{headers, Header, Headers};
+pub use front_matter::{front_matter, FrontMatter};
+pub use header::{
+    headers, headers_with_options, headers_with_slugger, Container, Header, HeaderBuilder, Headers,
+    LevelError,
+};
+pub use inline::Inline;
+pub use normalize::{Emoji, Normalization};
+pub use options::ParserOptions;
+pub use outline::{Node, Outline, OutlineIntoIter, OutlineIter};
+pub use slug::{AnchorSlugger, Flavor, Slugger};
 pub use write::{Writer, WriterBuilder};
@@ -5,11 +5,19 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
 // distributed except according to those terms.
 
+use crate::emoji;
+use crate::entity;
 use regex::Regex;
+use std::borrow::Cow;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+use unicode_normalization::{is_nfc, is_nfd, is_nfkc, is_nfkd, UnicodeNormalization};
 
 lazy_static::lazy_static! {
     static ref HTML_TAG_RE: Regex = Regex::new("</?[^>]+>").unwrap();
 
+    // Regular expression representing an HTML comment, which can itself contain a `>`
+    static ref HTML_COMMENT_RE: Regex = Regex::new("(?s)<!--.*?-->").unwrap();
+
     // Regular expression representing characters, symbols, and punctuation marks to strip out of
     // the given text
     static ref INVALID_CHARS_RE: Regex = Regex::new(concat!(
@@ -21,28 +29,701 @@ lazy_static::lazy_static! {
         "]"
     ))
     .unwrap();
+
+    // Regular expression representing an entity or numeric character reference at the start of
+    // the given text (see:
+    // https://spec.commonmark.org/0.29/#entity-and-numeric-character-references)
+    static ref CHAR_REF_RE: Regex =
+        Regex::new("^&(?:#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[A-Za-z][A-Za-z0-9]{1,31});").unwrap();
+
+    // Regular expression representing a trailing attribute block, such as `{#custom-id .class}`
+    // (Pandoc, Hugo) or `{: #custom-id}` (kramdown)
+    static ref ATTRIBUTES_RE: Regex = Regex::new(r"\{:?([^{}]*)\}\s*$").unwrap();
+
+    // Regular expression representing an emoji shortcode, such as `:rocket:` or `:+1:`
+    static ref SHORTCODE_RE: Regex = Regex::new(r":[a-z0-9_+\-]+:").unwrap();
+}
+
+/// How emoji are written in the title of a heading.
+///
+/// Emoji can appear in a heading either as a shortcode (such as `:rocket:`), which renderers like
+/// GitHub and GitLab display as an emoji, or directly as a Unicode emoji (such as `🚀`). Only the
+/// shortcodes which name a known emoji are recognized, and any emoji inside of code spans are left
+/// untouched as they are displayed literally.
+///
+/// Note that this only affects titles: the anchor of a heading is always computed the way its
+/// renderer would, regardless of how emoji are written in the table of contents.
+///
+/// # Examples
+///
+/// ```rust
+/// use mtoc_parser::{headers, Emoji, Header};
+///
+/// let titles = |emoji| {
+///     headers("# :rocket: Quick start")
+///         .emoji(emoji)
+///         .map(Header::into_title)
+///         .collect::<Vec<_>>()
+/// };
+///
+/// assert_eq!(vec![":rocket: Quick start"], titles(Emoji::Keep));
+/// assert_eq!(vec!["🚀 Quick start"], titles(Emoji::Unicode));
+/// assert_eq!(vec!["Quick start"], titles(Emoji::Drop));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emoji {
+    /// Emoji are kept as written, whether as shortcodes or as Unicode emoji.
+    Keep,
+    /// Emoji shortcodes are rendered as their Unicode emoji.
+    Unicode,
+    /// Both emoji shortcodes and Unicode emoji are dropped.
+    Drop,
+}
+
+impl Default for Emoji {
+    fn default() -> Self {
+        Emoji::Keep
+    }
+}
+
+/// The Unicode normalization form applied to the titles and slugs of headings.
+///
+/// The same text can be written with different sequences of Unicode characters, for example, `é`
+/// can be typed as a single precomposed character or as an `e` followed by a combining acute
+/// accent, depending on the keyboard or operating system. Normalizing the text ensures that
+/// headings which look the same have the same slug, so that anchor links written elsewhere will
+/// not miss.
+///
+/// The default form is NFC, which composes characters without changing their meaning. The
+/// compatibility forms (NFKC and NFKD) also fold variants such as full-width letters (`Ａ`) into
+/// their plain counterparts (`A`).
+///
+/// # Examples
+///
+/// ```rust
+/// use mtoc_parser::{headers, Header, Normalization};
+///
+/// let anchors = |normalization| {
+///     headers("# Caf\u{0065}\u{0301}\n# Café\n# ＡＰＩ\n")
+///         .normalization(normalization)
+///         .map(Header::into_anchor)
+///         .collect::<Vec<_>>()
+/// };
+///
+/// assert_eq!(vec!["#café", "#café-1", "#ａｐｉ"], anchors(Normalization::Nfc));
+/// assert_eq!(vec!["#café", "#café-1", "#api"], anchors(Normalization::Nfkc));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    /// Normalization Form C (canonical composition).
+    Nfc,
+    /// Normalization Form D (canonical decomposition).
+    Nfd,
+    /// Normalization Form KC (compatibility composition).
+    Nfkc,
+    /// Normalization Form KD (compatibility decomposition).
+    Nfkd,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Normalization::Nfc
+    }
+}
+
+/// Applies the given Unicode normalization form to the text, borrowing it if it is already
+/// normalized.
+pub(crate) fn normalize_unicode(text: Cow<'_, str>, form: Normalization) -> Cow<'_, str> {
+    let normalized = match form {
+        Normalization::Nfc => is_nfc(&text),
+        Normalization::Nfd => is_nfd(&text),
+        Normalization::Nfkc => is_nfkc(&text),
+        Normalization::Nfkd => is_nfkd(&text),
+    };
+    if normalized {
+        return text;
+    }
+
+    Cow::Owned(match form {
+        Normalization::Nfc => text.nfc().collect(),
+        Normalization::Nfd => text.nfd().collect(),
+        Normalization::Nfkc => text.nfkc().collect(),
+        Normalization::Nfkd => text.nfkd().collect(),
+    })
+}
+
+pub(crate) fn titleize(text: &str, form: Normalization) -> Cow<'_, str> {
+    let title = strip_html(text);
+    let title = if is_collapsed(&title) {
+        title
+    } else {
+        Cow::Owned(title.split_whitespace().collect::<Vec<_>>().join(" "))
+    };
+
+    let title = match title {
+        Cow::Borrowed(title) => decode(title, Decode::Markdown),
+        Cow::Owned(title) => Cow::Owned(decode(&title, Decode::Markdown).into_owned()),
+    };
+
+    normalize_unicode(title, form)
+}
+
+/// Returns the text with any HTML comments and tags removed.
+fn strip_html(text: &str) -> Cow<'_, str> {
+    match HTML_COMMENT_RE.replace_all(text, "") {
+        Cow::Borrowed(text) => HTML_TAG_RE.replace_all(text, ""),
+        Cow::Owned(text) => Cow::Owned(HTML_TAG_RE.replace_all(&text, "").into_owned()),
+    }
+}
+
+/// Returns whether the text has no leading or trailing whitespace and only single spaces between
+/// words, meaning that collapsing its whitespace would leave it unchanged.
+fn is_collapsed(text: &str) -> bool {
+    let mut prev_space = true;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if prev_space || c != ' ' {
+                return false;
+            }
+            prev_space = true;
+        } else {
+            prev_space = false;
+        }
+    }
+    !prev_space || text.is_empty()
+}
+
+/// Writes the emoji of a title in the given style.
+///
+/// Shortcodes which do not name a known emoji (such as the `:30:` in `10:30:00`) and the contents
+/// of code spans are left untouched. When emoji are dropped, the whitespace around them is
+/// collapsed so that the title has no leading, trailing or repeated spaces.
+pub(crate) fn emojify(title: Cow<'_, str>, emoji: Emoji) -> Cow<'_, str> {
+    if emoji == Emoji::Keep {
+        return title;
+    }
+
+    let bytes = title.as_bytes();
+    let mut emojified = String::new();
+    // The start of the text which has not yet been copied into `emojified`
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if bytes.get(i + 1).map_or(false, u8::is_ascii_punctuation) => i += 2,
+            b'`' => {
+                let len = backtick_run(bytes, i);
+                // Skip over the entire code span, as emoji are displayed literally inside of it
+                i = closing_backticks(bytes, i + len, len).unwrap_or(i) + len;
+            }
+            _ => {
+                let end = next_code_span(bytes, i);
+                if let Some(text) = emojify_text(&title[i..end], emoji) {
+                    emojified.push_str(&title[start..i]);
+                    emojified.push_str(&text);
+                    start = end;
+                }
+                i = end;
+            }
+        }
+    }
+
+    if start == 0 {
+        return title;
+    }
+    emojified.push_str(&title[start..]);
+    if emoji == Emoji::Drop && !is_collapsed(&emojified) {
+        emojified = emojified.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+
+    Cow::Owned(emojified)
+}
+
+/// Returns the offset of the next backslash or backtick, or the end of the text.
+fn next_code_span(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&b| b == b'\\' || b == b'`')
+        .map_or(bytes.len(), |pos| start + pos)
+        .max(start + 1)
+}
+
+/// Writes the emoji of text which contains no code spans in the given style, returning `None` if
+/// the text is unchanged.
+fn emojify_text(text: &str, emoji: Emoji) -> Option<String> {
+    let mut emojified = String::new();
+    let mut start = 0;
+    let mut search = 0;
+
+    while let Some(m) = SHORTCODE_RE.find_at(text, search) {
+        match emoji::get_by_shortcode(&text[m.start() + 1..m.end() - 1]) {
+            Some(found) => {
+                emojified.push_str(&text[start..m.start()]);
+                if emoji == Emoji::Unicode {
+                    emojified.push_str(found);
+                }
+                start = m.end();
+                search = m.end();
+            }
+            // The closing colon may open the next shortcode, as in `a:b:rocket:`
+            None => search = m.end() - 1,
+        }
+    }
+    emojified.push_str(&text[start..]);
+
+    if emoji == Emoji::Drop {
+        emojified = strip_emoji(&emojified);
+    }
+
+    if emojified == text {
+        None
+    } else {
+        Some(emojified)
+    }
+}
+
+/// Returns whether a character is an emoji when followed by the given character.
+///
+/// Characters which are displayed as text by default (such as `©`) are only emoji when followed
+/// by an emoji variation selector. Joiners, skin tone modifiers, keycaps, regional indicators (as
+/// used in flags) and tags are also treated as emoji as they only appear in emoji sequences.
+fn is_emoji(c: char, next: Option<char>) -> bool {
+    match c {
+        '\u{200D}' | '\u{FE0F}' | '\u{20E3}' => true,
+        '\u{1F3FB}'..='\u{1F3FF}' | '\u{1F1E6}'..='\u{1F1FF}' | '\u{E0020}'..='\u{E007F}' => true,
+        c if c.is_ascii() => false,
+        c => {
+            emoji::is_emoji_presentation(c)
+                || (next == Some('\u{FE0F}') && emoji::is_text_presentation(c))
+        }
+    }
+}
+
+/// Returns the text with any Unicode emoji removed.
+fn strip_emoji(text: &str) -> String {
+    let mut chars = text.chars().peekable();
+    let mut stripped = String::with_capacity(text.len());
+
+    while let Some(c) = chars.next() {
+        if !is_emoji(c, chars.peek().cloned()) {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Returns the plain text content of an HTML fragment, with its tags stripped, its character
+/// references decoded and its whitespace collapsed.
+pub(crate) fn html_text(html: &str) -> String {
+    let text = strip_html(html);
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    decode(&text, Decode::Text).into_owned()
+}
+
+/// Splits a trailing attribute block (such as `{#custom-id}`) from heading text.
+///
+/// Returns `None` if the text has no attribute block, otherwise returns the text before the block
+/// (without trailing whitespace) and the identifier given in the block, if any. A block is only
+/// recognized if each of its entries is an identifier (`#id`), a class (`.class`), a key/value pair
+/// (`key=value`) or the `-` shorthand for an unnumbered heading.
+pub(crate) fn split_attributes(text: &str) -> Option<(&str, Option<&str>)> {
+    let caps = ATTRIBUTES_RE.captures(text)?;
+    let mut id = None;
+    let mut entries = 0;
+
+    for entry in caps.get(1)?.as_str().split_whitespace() {
+        if entry.starts_with('#') && entry.len() > 1 {
+            id = Some(&entry[1..]);
+        } else if !is_attribute(entry) {
+            return None;
+        }
+        entries += 1;
+    }
+
+    if entries == 0 {
+        None
+    } else {
+        let start = caps.get(0)?.start();
+        Some((text[..start].trim_end(), id))
+    }
+}
+
+/// Returns whether an attribute block entry is a class, a key/value pair or the `-` shorthand.
+fn is_attribute(entry: &str) -> bool {
+    (entry.starts_with('.') && entry.len() > 1) || entry.contains('=') || entry == "-"
 }
 
-pub(crate) fn titleize<T: AsRef<str>>(text: T) -> String {
-    HTML_TAG_RE
-        .replace_all(text.as_ref(), "")
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ")
+/// How Markdown text is decoded by `decode`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Decode {
+    /// Decodes only the character references which are read literally in Markdown, keeping any
+    /// backslash escapes, so that the decoded text renders the same as the original.
+    Markdown,
+    /// Decodes all character references and backslash escapes, producing plain text.
+    Text,
 }
 
-pub(crate) fn slugify<T: AsRef<str>>(text: T) -> String {
-    let slug = text.as_ref().to_lowercase().trim().replace(" ", "-");
-    let slug = HTML_TAG_RE.replace_all(&slug, "");
-    // TODO: strip emphasis/strong markings
+/// Decodes the character references (such as `&amp;`, `&#38;` or `&#x26;`) and backslash escapes
+/// in Markdown text, as defined by the [CommonMark spec]. The contents of code spans are left
+/// untouched.
+///
+/// [CommonMark spec]: https://spec.commonmark.org/0.29/#backslash-escapes
+fn decode(text: &str, mode: Decode) -> Cow<'_, str> {
+    let bytes = text.as_bytes();
+    let mut decoded = String::new();
+    let mut changed = false;
+    // The start of the text which has been scanned but not yet copied into `decoded`
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if bytes.get(i + 1).map_or(false, u8::is_ascii_punctuation) => {
+                if mode == Decode::Text {
+                    decoded.push_str(&text[start..i]);
+                    changed = true;
+                    start = i + 1;
+                }
+                i += 2;
+            }
+            b'`' => {
+                let len = backtick_run(bytes, i);
+                i = match closing_backticks(bytes, i + len, len) {
+                    Some(end) => end + len,
+                    None => i + len,
+                };
+            }
+            b'&' => match CHAR_REF_RE.find(&text[i..]) {
+                Some(reference) => {
+                    let end = i + reference.end();
+                    match decode_char_ref(reference.as_str()) {
+                        Some(ref c) if mode == Decode::Text || is_literal(c, &text[end..]) => {
+                            decoded.push_str(&text[start..i]);
+                            decoded.push_str(c);
+                            changed = true;
+                            start = end;
+                        }
+                        _ => (),
+                    }
+                    i = end;
+                }
+                None => i += 1,
+            },
+            _ => i += 1,
+        }
+    }
+
+    if changed {
+        decoded.push_str(&text[start..]);
+        Cow::Owned(decoded)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Decodes a single character reference, returning `None` if it is not a valid reference.
+///
+/// As with the Markdown parser, a numeric reference to `0` is decoded as the replacement character
+/// and a numeric reference which is not a Unicode scalar value is not a valid reference.
+fn decode_char_ref(reference: &str) -> Option<String> {
+    let name = &reference[1..reference.len() - 1];
+    if !name.starts_with('#') {
+        return entity::get(name).map(String::from);
+    }
+
+    let code = if name.starts_with("#x") || name.starts_with("#X") {
+        u32::from_str_radix(&name[2..], 16)
+    } else {
+        name[1..].parse()
+    };
+    match code.ok()? {
+        0 => Some(String::from("\u{FFFD}")),
+        code => std::char::from_u32(code).map(|c| c.to_string()),
+    }
+}
+
+/// Returns whether the decoded text of a character reference is still read literally in Markdown
+/// when followed by the given text, rather than as emphasis, a code span, a link, HTML, etc.
+fn is_literal(decoded: &str, rest: &str) -> bool {
+    match decoded {
+        "&" => !CHAR_REF_RE.is_match(&["&", rest].concat()),
+        "<" => !rest.starts_with(|c: char| c.is_ascii_alphabetic() || "/!?".contains(c)),
+        _ => !decoded.chars().any(|c| r"\`*_[]~|!".contains(c)),
+    }
+}
+
+/// Escapes text for use as the text of a Markdown link, such as an entry in a table of contents.
+///
+/// Brackets, pipes (which would otherwise split a table cell), backslashes which do not start a
+/// backslash escape, and backticks which do not open or close a code span are escaped with a
+/// backslash. Existing backslash escapes are left untouched, as are the contents of code spans
+/// except for pipes, since a table splits its cells before any code span is parsed.
+pub(crate) fn escape_link_text(text: &str) -> Cow<'_, str> {
+    let bytes = text.as_bytes();
+    let mut escaped = String::new();
+    // The start of the text which has been scanned but not yet copied into `escaped`
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if bytes.get(i + 1).map_or(false, u8::is_ascii_punctuation) => i += 2,
+            b'\\' | b'[' | b']' | b'|' => {
+                escaped.push_str(&text[start..i]);
+                escaped.push('\\');
+                start = i;
+                i += 1;
+            }
+            b'`' => {
+                let len = backtick_run(bytes, i);
+                match closing_backticks(bytes, i + len, len) {
+                    // Skip over the entire code span, as escapes are not honored inside of it
+                    Some(end) => {
+                        for pipe in (i + len..end).filter(|&j| bytes[j] == b'|') {
+                            escaped.push_str(&text[start..pipe]);
+                            escaped.push('\\');
+                            start = pipe;
+                        }
+                        i = end + len;
+                    }
+                    None => {
+                        escaped.push_str(&text[start..i]);
+                        for _ in 0..len {
+                            escaped.push_str("\\`");
+                        }
+                        i += len;
+                        start = i;
+                    }
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    if escaped.is_empty() {
+        Cow::Borrowed(text)
+    } else {
+        escaped.push_str(&text[start..]);
+        Cow::Owned(escaped)
+    }
+}
+
+/// Returns the number of consecutive backticks starting at the given offset.
+fn backtick_run(bytes: &[u8], start: usize) -> usize {
+    bytes[start..].iter().take_while(|&&b| b == b'`').count()
+}
+
+/// Returns the offset of the next run of exactly `len` backticks, which closes a code span.
+fn closing_backticks(bytes: &[u8], mut i: usize, len: usize) -> Option<usize> {
+    while i < bytes.len() {
+        if bytes[i] == b'`' {
+            let run = backtick_run(bytes, i);
+            if run == len {
+                return Some(i);
+            }
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Returns a slug for the rendered text content of a heading.
+///
+/// The text is not Markdown source: emphasis markers, inline HTML and escapes have already been
+/// consumed by the parser, so any remaining `<` or `>` characters are literal text and are not
+/// treated as HTML tags.
+///
+/// As on GitHub, Unicode emoji are dropped from the slug while the text of emoji shortcodes is
+/// kept, so `:rocket: Launch` becomes `rocket-launch` and `🚀 Launch` becomes `-launch`. The text
+/// is expected to be normalized to a Unicode normalization form already.
+pub(crate) fn slugify_text(text: &str) -> String {
+    let slug = strip_emoji(text).to_lowercase().replace(" ", "-");
     let slug = INVALID_CHARS_RE.replace_all(&slug, "");
 
     slug.to_string()
 }
 
+/// Returns a slug for the rendered text content of a heading, the same way as GitLab.
+///
+/// GitLab only keeps word characters (letters, marks, numbers and underscores), dashes and spaces,
+/// so Unicode emoji are removed while the text of an emoji shortcode is kept without its colons.
+/// Spaces are replaced with dashes and any run of dashes is collapsed into one. A slug which is
+/// only made up of digits would conflict with a link to an issue, so it is prefixed with
+/// `anchor-`.
+pub(crate) fn slugify_gitlab(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+
+    for c in text.trim().to_lowercase().chars() {
+        let c = match c {
+            ' ' | '-' => '-',
+            c if is_word(c) => c,
+            _ => continue,
+        };
+        if c != '-' || !slug.ends_with('-') {
+            slug.push(c);
+        }
+    }
+
+    if !slug.is_empty() && slug.chars().all(|c| c.is_ascii_digit()) {
+        slug.insert_str(0, "anchor-");
+    }
+    slug
+}
+
+/// Returns a slug for the rendered text content of a heading, the same way as Bitbucket.
+///
+/// Bitbucket decomposes the text (in the NFKD normalization form) and drops any character which is
+/// not ASCII, so accented letters lose their accents and other scripts are removed entirely. Then
+/// only ASCII letters, digits, underscores, dashes and whitespace are kept, the text is trimmed and
+/// lowercased, and each run of dashes and whitespace is replaced with a single dash. Finally, the
+/// slug is prefixed with `markdown-header-`.
+pub(crate) fn slugify_bitbucket(text: &str) -> String {
+    let text = text
+        .nfkd()
+        .filter(|c| c.is_ascii_alphanumeric() || c.is_ascii_whitespace() || *c == '_' || *c == '-')
+        .collect::<String>();
+    let mut slug = String::from("markdown-header-");
+    let mut separated = false;
+
+    for c in text.trim().chars() {
+        if c == '-' || c.is_ascii_whitespace() {
+            if !separated {
+                slug.push('-');
+            }
+            separated = true;
+        } else {
+            slug.push(c.to_ascii_lowercase());
+            separated = false;
+        }
+    }
+    slug
+}
+
+/// Returns an identifier for the rendered text content of a heading, the same way as Pandoc's
+/// `auto_identifiers` extension.
+///
+/// Only letters, numbers, underscores, dashes, periods and whitespace are kept, the text is
+/// lowercased, and each run of whitespace is replaced with a single dash. Everything up to the
+/// first letter is removed, since an identifier may not begin with a number or punctuation, and
+/// an identifier which is left empty falls back to `section`.
+///
+/// With `ascii` (the `ascii_identifiers` extension), accented letters lose their accents and any
+/// other characters which are not ASCII are removed before everything up to the first letter is.
+pub(crate) fn slugify_pandoc(text: &str, ascii: bool) -> String {
+    let text = text
+        .to_lowercase()
+        .chars()
+        .filter(|&c| c.is_whitespace() || c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+        .collect::<String>();
+    let slug = text.split_whitespace().collect::<Vec<_>>().join("-");
+    let slug = if ascii { strip_accents(&slug) } else { slug };
+    let slug = slug.trim_start_matches(|c: char| !c.is_alphabetic());
+
+    if slug.is_empty() {
+        String::from("section")
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Returns an identifier for the rendered text content of a heading, the same way as Pandoc's
+/// `gfm_auto_identifiers` extension.
+///
+/// This follows GitHub more closely than `slugify_pandoc`: only word characters (letters, marks,
+/// numbers and underscores), dashes and whitespace are kept, the text is lowercased, and each
+/// whitespace character is replaced with a dash, so leading numbers and repeated dashes are kept.
+/// As with `auto_identifiers`, an identifier which is left empty falls back to `section`.
+pub(crate) fn slugify_pandoc_gfm(text: &str) -> String {
+    let slug = text
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            '-' => Some('-'),
+            c if c.is_whitespace() => Some('-'),
+            c if is_word(c) => Some(c),
+            _ => None,
+        })
+        .collect::<String>();
+
+    if slug.is_empty() {
+        String::from("section")
+    } else {
+        slug
+    }
+}
+
+/// Returns an identifier for the rendered text content of a heading, the same way as kramdown
+/// (which renders GitHub Pages sites with Jekyll).
+///
+/// Everything up to the first ASCII letter is removed, then only ASCII letters, digits, spaces and
+/// dashes are kept, so letters which are not ASCII (even accented ones) are removed rather than
+/// kept or transliterated. Spaces are replaced with dashes without being collapsed, the text is
+/// lowercased, and an identifier which is left empty falls back to `section`.
+pub(crate) fn slugify_kramdown(text: &str) -> String {
+    let slug = text
+        .trim_start_matches(|c: char| !c.is_ascii_alphabetic())
+        .chars()
+        .filter_map(|c| match c {
+            ' ' | '-' => Some('-'),
+            c if c.is_ascii_alphanumeric() => Some(c.to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect::<String>();
+
+    if slug.is_empty() {
+        String::from("section")
+    } else {
+        slug
+    }
+}
+
+/// Returns the text with the accents removed from any letters, dropping any other characters
+/// which are not ASCII.
+///
+/// A character is kept only if its canonical decomposition is an ASCII character followed by
+/// combining marks, in which case only the ASCII character is kept.
+fn strip_accents(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| {
+            let mut decomposed = Vec::new();
+            decompose_canonical(c, |d| decomposed.push(d));
+
+            match decomposed.split_first() {
+                Some((base, marks))
+                    if base.is_ascii() && marks.iter().all(|&m| is_combining_mark(m)) =>
+                {
+                    Some(*base)
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Returns whether a character is a word character, that is, a letter, a mark, a number or a
+/// connector punctuation (such as `_`).
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric()
+        || is_combining_mark(c)
+        || match c {
+            '_' | '\u{203F}' | '\u{2040}' | '\u{2054}' | '\u{FE33}' | '\u{FE34}' => true,
+            '\u{FE4D}'..='\u{FE4F}' | '\u{FF3F}' => true,
+            _ => false,
+        }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{slugify, titleize};
+    use super::{
+        emojify, escape_link_text, normalize_unicode, slugify_bitbucket, slugify_gitlab,
+        slugify_kramdown, slugify_pandoc, slugify_pandoc_gfm, slugify_text, split_attributes,
+        titleize, Emoji, Normalization,
+    };
+    use crate::headers;
 
     macro_rules! test {
         (
@@ -50,8 +731,11 @@ mod tests {
         ) => {
             #[test]
             fn $name() {
-                let titleize = titleize($title);
-                let slugify = slugify($title);
+                let titleize = titleize($title, Normalization::default());
+                let anchor = headers(&format!("# {}", $title))
+                    .next()
+                    .unwrap()
+                    .into_anchor();
 
                 assert_eq!(
                     $text_exp, titleize,
@@ -59,14 +743,115 @@ mod tests {
                     $title, $text_exp, titleize
                 );
                 assert_eq!(
-                    $anchor_exp, slugify,
-                    "slugify({:?}) != {:?} (got: {:?})",
-                    $title, $anchor_exp, slugify
+                    format!("#{}", $anchor_exp),
+                    anchor,
+                    "anchor of {:?} != {:?} (got: {:?})",
+                    $title,
+                    $anchor_exp,
+                    anchor
                 );
             }
         };
     }
 
+    macro_rules! test_heading {
+        (
+            $name:ident, $heading:expr, $anchor_exp:expr
+        ) => {
+            #[test]
+            fn $name() {
+                let md = format!("# {}", $heading);
+                let anchor = headers(&md).next().unwrap().into_anchor();
+
+                assert_eq!(
+                    format!("#{}", $anchor_exp),
+                    anchor,
+                    "anchor of {:?} != {:?} (got: {:?})",
+                    $heading,
+                    $anchor_exp,
+                    anchor
+                );
+            }
+        };
+    }
+
+    mod attributes {
+        use super::*;
+
+        #[test]
+        fn identifiers() {
+            assert_eq!(
+                Some(("Title", Some("custom-id"))),
+                split_attributes("Title {#custom-id}")
+            );
+            assert_eq!(
+                Some(("Title", Some("kramdown"))),
+                split_attributes("Title {: #kramdown .class}")
+            );
+            assert_eq!(
+                Some(("Title", Some("pandoc"))),
+                split_attributes("Title {#pandoc .unnumbered lang=en}  ")
+            );
+        }
+
+        #[test]
+        fn without_identifier() {
+            assert_eq!(
+                Some(("Title", None)),
+                split_attributes("Title {.unnumbered}")
+            );
+            assert_eq!(Some(("Title", None)), split_attributes("Title {-}"));
+        }
+
+        #[test]
+        fn not_attributes() {
+            assert_eq!(None, split_attributes("Title"));
+            assert_eq!(None, split_attributes("Title {}"));
+            assert_eq!(None, split_attributes("Header {something}"));
+            assert_eq!(None, split_attributes("Title {#id} more"));
+            assert_eq!(None, split_attributes("Title {# id}"));
+        }
+    }
+
+    mod escape {
+        use super::*;
+
+        #[test]
+        fn plain_text_is_borrowed() {
+            assert_eq!("Plain Title", escape_link_text("Plain Title"));
+        }
+
+        #[test]
+        fn brackets_and_pipes() {
+            assert_eq!(r"Array\[T\] access", escape_link_text("Array[T] access"));
+            assert_eq!(r"\[1\]", escape_link_text("[1]"));
+            assert_eq!(r"A \| B", escape_link_text("A | B"));
+        }
+
+        #[test]
+        fn backslashes() {
+            assert_eq!(r"Use \\ for paths", escape_link_text(r"Use \\ for paths"));
+            assert_eq!(r"Path C:\\", escape_link_text(r"Path C:\"));
+            assert_eq!(r"C:\\Windows", escape_link_text(r"C:\Windows"));
+            assert_eq!(r"Literal \*star\*", escape_link_text(r"Literal \*star\*"));
+            assert_eq!(r"\[1\]", escape_link_text(r"\[1]"));
+        }
+
+        #[test]
+        fn code_spans_only_escape_pipes() {
+            assert_eq!(r"The `a[0]\|b` span", escape_link_text("The `a[0]|b` span"));
+            assert_eq!(r"`a \|\| b` \| c", escape_link_text("`a || b` | c"));
+            assert_eq!("``a ` b``", escape_link_text("``a ` b``"));
+            assert_eq!(r"`C:\`", escape_link_text(r"`C:\`"));
+        }
+
+        #[test]
+        fn unbalanced_backticks() {
+            assert_eq!(r"A \` tick", escape_link_text("A ` tick"));
+            assert_eq!(r"\`\`a\` \[b\]", escape_link_text("``a` [b]"));
+        }
+    }
+
     mod fnichol {
         use super::*;
 
@@ -92,10 +877,17 @@ mod tests {
         //     "on-go-to-top"
         // );
 
+        test_heading!(
+            sub_sub_link_anchor,
+            "On <sub><sub>[Go to Top](#top)</sub></sub>",
+            "on-go-to-top"
+        );
+
         test!(
             lowercase_diacritics,
             "Okay Åô Then",
-            "Okay Åô Then",
+            // The Angstrom sign is canonically equivalent to (and composes to) `Å`
+            "Okay Åô Then",
             "okay-åô-then"
         );
     }
@@ -326,6 +1118,253 @@ mod tests {
         );
     }
 
+    // Slugs are computed from the rendered text content of a heading, so emphasis and strong
+    // markers are dropped while any literal underscores or asterisks are kept.
+    mod emphasis {
+        use super::*;
+
+        test_heading!(
+            emphasis_and_strong,
+            "The *really* __important__ part",
+            "the-really-important-part"
+        );
+        test_heading!(nested_emphasis, "**Bold _and_ italic**", "bold-and-italic");
+        test_heading!(strong_underscores, "__init__ method", "init-method");
+        test_heading!(snake_case_words, "snake_case_words", "snake_case_words");
+        test_heading!(
+            emphasis_in_code_span,
+            "The `__init__` method",
+            "the-__init__-method"
+        );
+        test_heading!(escaped_underscores, r"\_not emphasis\_", "_not-emphasis_");
+        test_heading!(inline_html_dropped, "<em>Mixed</em> *Case*", "mixed-case");
+    }
+
+    // HTML comments are dropped as a whole, even if they contain a `>` which would otherwise end
+    // an HTML tag.
+    mod comments {
+        use super::*;
+
+        test!(trailing, "API <!-- draft -->", "API", "api-");
+        test!(
+            containing_gt,
+            "Usage <!-- a > b --> Guide",
+            "Usage Guide",
+            "usage--guide"
+        );
+        test!(multiple, "<!-- x -->Setup<!-- y -->", "Setup", "setup");
+    }
+
+    // Emoji shortcodes are kept, rendered or dropped in titles while slugs follow GitHub, which
+    // drops Unicode emoji but keeps the text of shortcodes.
+    mod emoji {
+        use super::*;
+
+        fn emojified(title: &str, emoji: Emoji) -> String {
+            emojify(title.into(), emoji).into_owned()
+        }
+
+        #[test]
+        fn keep() {
+            assert_eq!(
+                ":rocket: Quick 🚀",
+                emojified(":rocket: Quick 🚀", Emoji::Keep)
+            );
+        }
+
+        #[test]
+        fn unicode() {
+            assert_eq!(
+                "🚀 Quick start",
+                emojified(":rocket: Quick start", Emoji::Unicode)
+            );
+            assert_eq!("👍👎", emojified(":+1::-1:", Emoji::Unicode));
+        }
+
+        #[test]
+        fn drop() {
+            assert_eq!(
+                "Quick start",
+                emojified(":rocket: Quick start", Emoji::Drop)
+            );
+            assert_eq!("Quick start", emojified("Quick 🚀 start ✨", Emoji::Drop));
+            assert_eq!("Flags", emojified("🇨🇦 Flags 👩‍💻", Emoji::Drop));
+        }
+
+        #[test]
+        fn unknown_shortcodes() {
+            assert_eq!(
+                "At 10:30:00 :nope:",
+                emojified("At 10:30:00 :nope:", Emoji::Unicode)
+            );
+            assert_eq!("a:nope🚀", emojified("a:nope:rocket:", Emoji::Unicode));
+        }
+
+        #[test]
+        fn code_spans_are_untouched() {
+            assert_eq!(
+                "`:rocket:` 🚀 `🚀`",
+                emojified("`:rocket:` :rocket: `🚀`", Emoji::Unicode)
+            );
+            assert_eq!("`🚀` x", emojified("`🚀` 🚀 x", Emoji::Drop));
+        }
+
+        #[test]
+        fn text_presentation() {
+            assert_eq!("© 2019", emojified("© 2019", Emoji::Drop));
+            assert_eq!("2019", emojified("©\u{FE0F} 2019", Emoji::Drop));
+        }
+
+        test!(
+            unicode_slug,
+            "🚀 Quick start",
+            "🚀 Quick start",
+            "-quick-start"
+        );
+        test!(
+            shortcode_slug,
+            ":rocket: Quick start",
+            ":rocket: Quick start",
+            "rocket-quick-start"
+        );
+        test_heading!(heading_unicode, "🚀 Quick start", "-quick-start");
+        test_heading!(
+            heading_shortcode,
+            ":rocket: Quick start",
+            "rocket-quick-start"
+        );
+        test_heading!(heading_sequence, "Team 👩🏽‍💻 Notes", "team--notes");
+        test_heading!(heading_text_presentation, "&copy; 2019", "©-2019");
+    }
+
+    // Headings are normalized to a Unicode normalization form (NFC by default) so that text which
+    // looks the same has the same slug, however it was typed.
+    //
+    // Reference: https://unicode.org/reports/tr15/
+    mod unicode {
+        use super::*;
+
+        #[test]
+        fn combining_marks() {
+            let composed = "Caf\u{E9} Menu";
+            let decomposed = "Cafe\u{301} Menu";
+
+            assert_eq!(composed, titleize(decomposed, Normalization::Nfc));
+            assert_eq!(decomposed, titleize(composed, Normalization::Nfd));
+            assert_eq!(
+                slugify_text(&normalize_unicode(composed.into(), Normalization::Nfc)),
+                slugify_text(&normalize_unicode(decomposed.into(), Normalization::Nfc))
+            );
+            assert_eq!(
+                "café-menu",
+                slugify_text(&normalize_unicode(decomposed.into(), Normalization::Nfc))
+            );
+        }
+
+        #[test]
+        fn hangul() {
+            // The conjoining jamo ᄀ, ᅡ and ᆨ compose into the syllable 각
+            let jamo = "\u{1100}\u{1161}\u{11A8} Title";
+
+            assert_eq!("\u{AC01} Title", titleize(jamo, Normalization::Nfc));
+            assert_eq!(jamo, titleize("\u{AC01} Title", Normalization::Nfd));
+            assert_eq!(
+                "\u{AC01}-title",
+                slugify_text(&normalize_unicode(jamo.into(), Normalization::Nfkc))
+            );
+        }
+
+        #[test]
+        fn full_width_forms() {
+            let full_width = "ＡＰＩ Ｇｕｉｄｅ１";
+
+            assert_eq!(full_width, titleize(full_width, Normalization::Nfc));
+            assert_eq!("API Guide1", titleize(full_width, Normalization::Nfkc));
+            assert_eq!(
+                "ａｐｉ-ｇｕｉｄｅ１",
+                slugify_text(&normalize_unicode(full_width.into(), Normalization::Nfc))
+            );
+            assert_eq!(
+                "api-guide1",
+                slugify_text(&normalize_unicode(full_width.into(), Normalization::Nfkd))
+            );
+        }
+
+        #[test]
+        fn normalized_text_is_borrowed() {
+            match titleize("Café", Normalization::Nfc) {
+                std::borrow::Cow::Borrowed(_) => (),
+                std::borrow::Cow::Owned(_) => panic!("expected a borrowed title"),
+            }
+        }
+
+        test_heading!(heading_decomposed, "Cafe\u{301}", "café");
+    }
+
+    // Character references and backslash escapes are decoded as defined by the CommonMark spec,
+    // though a title keeps any references which would not be read literally once decoded.
+    //
+    // Reference:
+    // https://spec.commonmark.org/0.29/#entity-and-numeric-character-references
+    mod decode {
+        use super::*;
+
+        test!(named, "Tom &amp; Jerry", "Tom & Jerry", "tom--jerry");
+        test!(named_lt, "5 &lt; 6", "5 < 6", "5--6");
+        test!(named_non_ascii, "&copy; 2019", "© 2019", "©-2019");
+        test!(decimal, "Caf&#233; Menu", "Café Menu", "café-menu");
+        test!(hex, "Caf&#xE9; Menu", "Café Menu", "café-menu");
+        test!(hex_uppercase, "Caf&#XE9; Menu", "Café Menu", "café-menu");
+        test!(null, "A &#0; B", "A \u{FFFD} B", "a-\u{FFFD}-b");
+        test!(surrogate, "A &#xD800; B", "A &#xD800; B", "a-xd800-b");
+        test!(
+            unknown_reference,
+            "Use &bogus; here",
+            "Use &bogus; here",
+            "use-bogus-here"
+        );
+        test!(
+            reference_to_markup,
+            "&#42;not emphasis&#42;",
+            "&#42;not emphasis&#42;",
+            "not-emphasis"
+        );
+        test!(reference_to_html, "&lt;b&gt; tags", "&lt;b> tags", "b-tags");
+        test!(
+            reference_to_reference,
+            "&amp;amp; sign",
+            "&amp;amp; sign",
+            "amp-sign"
+        );
+        test!(
+            reference_in_code_span,
+            "`&amp;` code",
+            "`&amp;` code",
+            "amp-code"
+        );
+        test!(
+            escaped_reference,
+            r"\&amp; sign",
+            r"\&amp; sign",
+            "amp-sign"
+        );
+        test!(
+            backslash_escapes,
+            r"Literal \*star\*",
+            r"Literal \*star\*",
+            "literal-star"
+        );
+        test!(
+            escaped_underscores,
+            r"\_x\_ test",
+            r"\_x\_ test",
+            "_x_-test"
+        );
+        test_heading!(heading_named, "Tom &amp; Jerry", "tom--jerry");
+        test_heading!(heading_hex, "Caf&#xE9; &#x26; Bar", "café--bar");
+        test_heading!(heading_escapes, r"Literal \*star\*", "literal-star");
+    }
+
     // Tests were adapted from the https://github.com/naokazuterada/MarkdownTOC test suite and
     // altered/updated/modified where appropriate.
     //
@@ -354,20 +1393,18 @@ mod tests {
             "Heading &and&and& 3",
             "heading-andand-3"
         );
-        // TODO: support escaped HTML output as GitHub appears to support this
-        //
-        // test!(
-        //     replace_chars_5,
-        //     "&lt;element1>",
-        //     "&lt;element1>",
-        //     "element1"
-        // );
-        // test!(
-        //     replace_chars_6,
-        //     "&#60;element2>",
-        //     "&#60;element2>",
-        //     "element2"
-        // );
+        test!(
+            replace_chars_5,
+            "&lt;element1>",
+            "&lt;element1>",
+            "element1"
+        );
+        test!(
+            replace_chars_6,
+            "&#60;element2>",
+            "&#60;element2>",
+            "element2"
+        );
         test!(
             no_escape_in_code_with_links_1,
             "`function(param, [optional])`",
@@ -398,15 +1435,7 @@ mod tests {
             "_x test 1",
             "_x-test-1"
         );
-        // TODO: support emphasis/strong sequences in the title text which would more fully support
-        // GitHub's extended Markdown format
-        //
-        // test!(
-        //     underscores_asterisks_head_2,
-        //     "_x_ test 2",
-        //     "_x_ test 2",
-        //     "x-test-2"
-        // );
+        test_heading!(underscores_asterisks_head_2, "_x_ test 2", "x-test-2");
         test!(
             underscores_asterisks_head_3,
             "*x* test 3",
@@ -437,15 +1466,7 @@ mod tests {
             "* x* test 7",
             "-x-test-7"
         );
-        // TODO: support emphasis/strong sequences in the title text which would more fully support
-        // GitHub's extended Markdown format
-        //
-        // test!(
-        //     underscores_asterisks_head_8,
-        //     "__x__ test 8",
-        //     "__x__ test 8",
-        //     "x-test-8"
-        // );
+        test_heading!(underscores_asterisks_head_8, "__x__ test 8", "x-test-8");
         test!(
             underscores_asterisks_head_9,
             "**x** test 9",
@@ -494,15 +1515,7 @@ mod tests {
             "1 test_x",
             "1-test_x"
         );
-        // TODO: support emphasis/strong sequences in the title text which would more fully support
-        // GitHub's extended Markdown format
-        //
-        // test!(
-        //     underscores_asterisks_tail_2,
-        //     "2 test _x_",
-        //     "2 test _x_",
-        //     "2-test-x"
-        // );
+        test_heading!(underscores_asterisks_tail_2, "2 test _x_", "2-test-x");
         test!(
             underscores_asterisks_tail_3,
             "3 test *x*",
@@ -533,15 +1546,7 @@ mod tests {
             "7 test * x*",
             "7-test--x"
         );
-        // TODO: support emphasis/strong sequences in the title text which would more fully support
-        // GitHub's extended Markdown format
-        //
-        // test!(
-        //     underscores_asterisks_tail_8,
-        //     "8 test __x__",
-        //     "8 test __x__",
-        //     "8-test-x"
-        // );
+        test_heading!(underscores_asterisks_tail_8, "8 test __x__", "8-test-x");
         test!(
             underscores_asterisks_tail_9,
             "9 test **x**",
@@ -590,15 +1595,7 @@ mod tests {
             "1_x test",
             "1_x-test"
         );
-        // TODO: support emphasis/strong sequences in the title text which would more fully support
-        // GitHub's extended Markdown format
-        //
-        // test!(
-        //     underscores_asterisks_middle_2,
-        //     "2 _x_ test",
-        //     "2 _x_ test",
-        //     "2-x-test"
-        // );
+        test_heading!(underscores_asterisks_middle_2, "2 _x_ test", "2-x-test");
         test!(
             underscores_asterisks_middle_3,
             "3 *x* test",
@@ -629,15 +1626,7 @@ mod tests {
             "7 * x* test",
             "7--x-test"
         );
-        // TODO: support emphasis/strong sequences in the title text which would more fully support
-        // GitHub's extended Markdown format
-        //
-        // test!(
-        //     underscores_asterisks_middle_8,
-        //     "8 __x__ test",
-        //     "8 __x__ test",
-        //     "8-x-test"
-        // );
+        test_heading!(underscores_asterisks_middle_8, "8 __x__ test", "8-x-test");
         test!(
             underscores_asterisks_middle_9,
             "9 **x** test",
@@ -682,6 +1671,336 @@ mod tests {
         );
     }
 
+    // Tests were adapted from the kramdown documentation and test suite and
+    // altered/updated/modified where appropriate. Each test checks the identifier of kramdown
+    // alongside the anchor of GitHub for the same heading, to show where they differ.
+    //
+    // Reference:
+    // https://kramdown.gettalong.org/converter/html.html#auto-ids
+    // https://github.com/gettalong/kramdown/blob/master/test/testcases/block/04_header/with_auto_ids.text
+    mod kramdown {
+        use super::*;
+
+        macro_rules! test_kramdown {
+            (
+                $name:ident, $text:expr, $kramdown_exp:expr, $github_exp:expr
+            ) => {
+                #[test]
+                fn $name() {
+                    let kramdown = slugify_kramdown($text);
+                    let github = slugify_text($text);
+
+                    assert_eq!(
+                        $kramdown_exp, kramdown,
+                        "slugify_kramdown({:?}) != {:?} (got: {:?})",
+                        $text, $kramdown_exp, kramdown
+                    );
+                    assert_eq!(
+                        $github_exp, github,
+                        "slugify_text({:?}) != {:?} (got: {:?})",
+                        $text, $github_exp, github
+                    );
+                }
+            };
+        }
+
+        test_kramdown!(
+            words,
+            "This is a header",
+            "this-is-a-header",
+            "this-is-a-header"
+        );
+        test_kramdown!(
+            punctuation,
+            "What's new in v2?",
+            "whats-new-in-v2",
+            "whats-new-in-v2"
+        );
+        test_kramdown!(symbols, "Do ^& it now", "do--it-now", "do--it-now");
+        test_kramdown!(
+            uncollapsed_dashes,
+            "Step 1 -- Install",
+            "step-1----install",
+            "step-1----install"
+        );
+        // kramdown strips everything up to the first letter, while GitHub keeps leading digits
+        test_kramdown!(
+            leading_digits,
+            "12. Another one 1 here",
+            "another-one-1-here",
+            "12-another-one-1-here"
+        );
+        test_kramdown!(only_digits, "23", "section", "23");
+        // kramdown removes letters which are not ASCII, while GitHub keeps them
+        test_kramdown!(accents, "Café Menu", "caf-menu", "café-menu");
+        test_kramdown!(leading_accent, "Übersicht", "bersicht", "übersicht");
+        test_kramdown!(non_ascii, "日本語", "section", "日本語");
+        test_kramdown!(mixed_scripts, "日本語 Guide", "guide", "日本語-guide");
+        // kramdown removes underscores, while GitHub keeps them
+        test_kramdown!(underscores, "snake_case", "snakecase", "snake_case");
+        test_kramdown!(emoji, "🚀 Launch", "launch", "-launch");
+        test_kramdown!(tabs, "Tabbed\tHeading", "tabbedheading", "tabbed\theading");
+        test_kramdown!(only_punctuation, "?!", "section", "");
+    }
+
+    // Tests were adapted from the Pandoc User's Guide and the Pandoc test suite and
+    // altered/updated/modified where appropriate. Each test checks the identifier of the
+    // `auto_identifiers`, `auto_identifiers` with `ascii_identifiers`, and `gfm_auto_identifiers`
+    // extensions in turn, to show where they differ.
+    //
+    // Reference:
+    // https://pandoc.org/MANUAL.html#extension-auto_identifiers
+    // https://github.com/jgm/pandoc/blob/master/test/command/4235.md
+    mod pandoc {
+        use super::*;
+
+        macro_rules! test_pandoc {
+            (
+                $name:ident, $text:expr, $auto_exp:expr, $ascii_exp:expr, $gfm_exp:expr
+            ) => {
+                #[test]
+                fn $name() {
+                    let auto = slugify_pandoc($text, false);
+                    let ascii = slugify_pandoc($text, true);
+                    let gfm = slugify_pandoc_gfm($text);
+
+                    assert_eq!(
+                        $auto_exp, auto,
+                        "slugify_pandoc({:?}, false) != {:?} (got: {:?})",
+                        $text, $auto_exp, auto
+                    );
+                    assert_eq!(
+                        $ascii_exp, ascii,
+                        "slugify_pandoc({:?}, true) != {:?} (got: {:?})",
+                        $text, $ascii_exp, ascii
+                    );
+                    assert_eq!(
+                        $gfm_exp, gfm,
+                        "slugify_pandoc_gfm({:?}) != {:?} (got: {:?})",
+                        $text, $gfm_exp, gfm
+                    );
+                }
+            };
+        }
+
+        test_pandoc!(
+            words,
+            "Heading identifiers in HTML",
+            "heading-identifiers-in-html",
+            "heading-identifiers-in-html",
+            "heading-identifiers-in-html"
+        );
+        test_pandoc!(
+            accents,
+            "Maître d'hôtel",
+            "maître-dhôtel",
+            "maitre-dhotel",
+            "maître-dhôtel"
+        );
+        test_pandoc!(
+            punctuation_and_dashes,
+            "Dogs?--in my house?",
+            "dogs--in-my-house",
+            "dogs--in-my-house",
+            "dogs--in-my-house"
+        );
+        test_pandoc!(
+            commas,
+            "HTML, S5, or RTF?",
+            "html-s5-or-rtf",
+            "html-s5-or-rtf",
+            "html-s5-or-rtf"
+        );
+        test_pandoc!(
+            leading_number,
+            "3. Applications",
+            "applications",
+            "applications",
+            "3-applications"
+        );
+        test_pandoc!(only_numbers, "33", "section", "section", "33");
+        test_pandoc!(
+            periods,
+            "Version 1.2",
+            "version-1.2",
+            "version-1.2",
+            "version-12"
+        );
+        test_pandoc!(
+            underscores,
+            "snake_case words",
+            "snake_case-words",
+            "snake_case-words",
+            "snake_case-words"
+        );
+        test_pandoc!(
+            multiple_spaces,
+            "  Spaced   out  ",
+            "spaced-out",
+            "spaced-out",
+            "--spaced---out--"
+        );
+        test_pandoc!(
+            non_ascii,
+            "日本語 Guide",
+            "日本語-guide",
+            "guide",
+            "日本語-guide"
+        );
+        test_pandoc!(
+            leading_punctuation,
+            "...And Then",
+            "and-then",
+            "and-then",
+            "and-then"
+        );
+        test_pandoc!(emoji, "🚀 Launch", "launch", "launch", "-launch");
+        test_pandoc!(only_punctuation, "?!", "section", "section", "section");
+    }
+
+    // Tests were adapted from the Python-Markdown table of contents extension, which generates the
+    // heading ids of Bitbucket, and the https://github.com/thlorenz/anchor-markdown-header test
+    // suite and altered/updated/modified where appropriate.
+    //
+    // Reference:
+    // https://github.com/Python-Markdown/markdown/blob/master/markdown/extensions/toc.py
+    // https://github.com/thlorenz/anchor-markdown-header/blob/master/test/anchor-markdown-header.js
+    mod bitbucket {
+        use super::*;
+
+        macro_rules! test_bitbucket {
+            (
+                $name:ident, $text:expr, $anchor_exp:expr
+            ) => {
+                #[test]
+                fn $name() {
+                    let slug = slugify_bitbucket($text);
+
+                    assert_eq!(
+                        $anchor_exp, slug,
+                        "slugify_bitbucket({:?}) != {:?} (got: {:?})",
+                        $text, $anchor_exp, slug
+                    );
+                }
+            };
+        }
+
+        test_bitbucket!(words, "Hello World", "markdown-header-hello-world");
+        test_bitbucket!(
+            punctuation,
+            "What's new in v2?",
+            "markdown-header-whats-new-in-v2"
+        );
+        test_bitbucket!(
+            collapsed_dashes,
+            "Step 1 -- Install",
+            "markdown-header-step-1-install"
+        );
+        test_bitbucket!(ampersand, "Tom & Jerry", "markdown-header-tom-jerry");
+        test_bitbucket!(symbols, "C++ & C#", "markdown-header-c-c");
+        test_bitbucket!(
+            underscores,
+            "snake_case_words",
+            "markdown-header-snake_case_words"
+        );
+        test_bitbucket!(accents, "Héllo Wörld", "markdown-header-hello-world");
+        test_bitbucket!(
+            compatibility,
+            "ﬁle ｓｙｓｔｅｍ",
+            "markdown-header-file-system"
+        );
+        test_bitbucket!(non_ascii, "日本語", "markdown-header-");
+        test_bitbucket!(mixed_scripts, "Русский Guide", "markdown-header-guide");
+        test_bitbucket!(emoji, "🚀 Launch", "markdown-header-launch");
+        test_bitbucket!(surrounding_spaces, "  Padded  ", "markdown-header-padded");
+        test_bitbucket!(tabs, "Tabbed\tHeading", "markdown-header-tabbed-heading");
+        test_bitbucket!(
+            leading_dash,
+            "-Leading dash",
+            "markdown-header--leading-dash"
+        );
+        test_bitbucket!(
+            trailing_dash,
+            "Trailing dash -",
+            "markdown-header-trailing-dash-"
+        );
+    }
+
+    // Tests were adapted from GitLab's heading id rules and its table of contents filter test suite
+    // and altered/updated/modified where appropriate.
+    //
+    // Reference:
+    // https://docs.gitlab.com/ee/user/markdown.html#heading-ids-and-links
+    // https://gitlab.com/gitlab-org/gitlab/-/blob/master/spec/lib/banzai/filter/table_of_contents_filter_spec.rb
+    mod gitlab {
+        use super::*;
+
+        macro_rules! test_gitlab {
+            (
+                $name:ident, $text:expr, $anchor_exp:expr
+            ) => {
+                #[test]
+                fn $name() {
+                    let slug = slugify_gitlab($text);
+
+                    assert_eq!(
+                        $anchor_exp, slug,
+                        "slugify_gitlab({:?}) != {:?} (got: {:?})",
+                        $text, $anchor_exp, slug
+                    );
+                }
+            };
+        }
+
+        test_gitlab!(
+            spaces,
+            "This heading has spaces in it",
+            "this-heading-has-spaces-in-it"
+        );
+        test_gitlab!(
+            unicode,
+            "This heading has a unicode in it: 한글",
+            "this-heading-has-a-unicode-in-it-한글"
+        );
+        test_gitlab!(
+            decimal_and_parentheses,
+            "This heading has 3.5 in it (and parentheses)",
+            "this-heading-has-35-in-it-and-parentheses"
+        );
+        test_gitlab!(
+            multiple_spaces_and_hyphens,
+            "This heading has  multiple spaces and --- hyphens",
+            "this-heading-has-multiple-spaces-and-hyphens"
+        );
+        test_gitlab!(lowercase, "Header", "header");
+        test_gitlab!(
+            punctuation,
+            "Ruby on Rails: A Guide!",
+            "ruby-on-rails-a-guide"
+        );
+        test_gitlab!(ampersand, "Cool Stuff & Things", "cool-stuff-things");
+        test_gitlab!(quotes, "Header with \"quotes\"", "header-with-quotes");
+        test_gitlab!(underscores, "snake_case_words", "snake_case_words");
+        test_gitlab!(accents, "Ünicode Ümlauts", "ünicode-ümlauts");
+        test_gitlab!(combining_marks, "नमस्ते", "नमस्ते");
+        test_gitlab!(surrounding_spaces, " Padded ", "padded");
+        test_gitlab!(digits_only, "123", "anchor-123");
+        test_gitlab!(digits_and_text, "1. Introduction", "1-introduction");
+        test_gitlab!(emoji, "🚀 Quick start", "-quick-start");
+        test_gitlab!(
+            emoji_shortcode,
+            "This heading has a :thumbsup: in it",
+            "this-heading-has-a-thumbsup-in-it"
+        );
+        test_gitlab!(
+            leading_emoji_shortcode,
+            ":rocket: Quick start",
+            "rocket-quick-start"
+        );
+        test_gitlab!(unknown_shortcode, ":nope: Quick start", "nope-quick-start");
+        test_gitlab!(only_punctuation, "!!!", "");
+    }
+
     // Tests were adapted from the https://github.com/jch/html-pipeline test suite and
     // altered/updated/modified where appropriate.
     //
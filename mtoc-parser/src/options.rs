@@ -0,0 +1,145 @@
+// Copyright 2019 Fletcher Nichol and/or applicable contributors.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license (see <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
+// distributed except according to those terms.
+
+use pulldown_cmark::{Options, Parser};
+
+/// The Markdown extensions which are enabled when parsing a document.
+///
+/// By default, a document is parsed as strict [CommonMark] with no extensions enabled. Each
+/// extension can be enabled individually, or the [`gfm`] preset can be used to parse a document
+/// in the same manner as [GitHub Flavored Markdown], which is how GitHub renders README files,
+/// issues, and comments.
+///
+/// Enabling the same extensions as the renderer of a document ensures that the headings are
+/// processed the same way, for example, that `~~struck~~` text in a heading is read as
+/// strikethrough rather than as literal tildes.
+///
+/// # Examples
+///
+/// ```rust
+/// use mtoc_parser::{headers_with_options, Header, ParserOptions};
+///
+/// let markdown = "# Old ~~and busted~~[^1]\n\n[^1]: A footnote.\n";
+///
+/// let anchors = headers_with_options(markdown, ParserOptions::gfm())
+///     .map(Header::into_anchor)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(vec!["#old-and-busted"], anchors);
+/// ```
+///
+/// [CommonMark]: https://commonmark.org/
+/// [GitHub Flavored Markdown]: https://github.github.com/gfm/
+/// [`gfm`]: #method.gfm
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ParserOptions {
+    tables: bool,
+    footnotes: bool,
+    strikethrough: bool,
+    task_lists: bool,
+}
+
+impl ParserOptions {
+    /// Builds a new `ParserOptions` with no extensions enabled, that is, for strict CommonMark.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a new `ParserOptions` with all of the extensions supported by [GitHub Flavored
+    /// Markdown] enabled: tables, footnotes, strikethrough, and task lists.
+    ///
+    /// [GitHub Flavored Markdown]: https://github.github.com/gfm/
+    pub fn gfm() -> Self {
+        ParserOptions {
+            tables: true,
+            footnotes: true,
+            strikethrough: true,
+            task_lists: true,
+        }
+    }
+
+    /// Sets whether tables are enabled.
+    pub fn tables(mut self, enabled: bool) -> Self {
+        self.tables = enabled;
+        self
+    }
+
+    /// Sets whether footnotes (such as `[^1]`) are enabled.
+    pub fn footnotes(mut self, enabled: bool) -> Self {
+        self.footnotes = enabled;
+        self
+    }
+
+    /// Sets whether strikethrough text (such as `~~struck~~`) is enabled.
+    pub fn strikethrough(mut self, enabled: bool) -> Self {
+        self.strikethrough = enabled;
+        self
+    }
+
+    /// Sets whether task list items (such as `- [x] done`) are enabled.
+    pub fn task_lists(mut self, enabled: bool) -> Self {
+        self.task_lists = enabled;
+        self
+    }
+
+    /// Returns a new Markdown parser for the given source with the enabled extensions.
+    pub(crate) fn parser<'a>(&self, buf: &'a str) -> Parser<'a> {
+        let mut options = Options::empty();
+        if self.tables {
+            options.insert(Options::ENABLE_TABLES);
+        }
+        if self.footnotes {
+            options.insert(Options::ENABLE_FOOTNOTES);
+        }
+        if self.strikethrough {
+            options.insert(Options::ENABLE_STRIKETHROUGH);
+        }
+        if self.task_lists {
+            options.insert(Options::ENABLE_TASKLISTS);
+        }
+
+        Parser::new_ext(buf, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Event, Tag};
+
+    fn has_strikethrough(options: ParserOptions) -> bool {
+        options
+            .parser("~~struck~~")
+            .any(|event| event == Event::Start(Tag::Strikethrough))
+    }
+
+    #[test]
+    fn default_is_commonmark() {
+        assert_eq!(ParserOptions::new(), ParserOptions::default());
+        assert!(!has_strikethrough(ParserOptions::new()));
+    }
+
+    #[test]
+    fn gfm_enables_all() {
+        assert_eq!(
+            ParserOptions::new()
+                .tables(true)
+                .footnotes(true)
+                .strikethrough(true)
+                .task_lists(true),
+            ParserOptions::gfm()
+        );
+        assert!(has_strikethrough(ParserOptions::gfm()));
+    }
+
+    #[test]
+    fn extensions_can_be_disabled() {
+        assert!(!has_strikethrough(
+            ParserOptions::gfm().strikethrough(false)
+        ));
+    }
+}
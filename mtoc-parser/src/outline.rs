@@ -0,0 +1,499 @@
+// Copyright 2019 Fletcher Nichol and/or applicable contributors.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license (see <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
+// distributed except according to those terms.
+
+use crate::Header;
+use std::iter::FromIterator;
+use std::slice;
+use std::vec;
+
+/// A hierarchical tree of [`Header`]s.
+///
+/// An `Outline` is built from any `Iterator` of `Header`s (such as a [`Headers`]) by collecting
+/// it. Each header becomes a child of the nearest preceding header with a lower level number, so
+/// gaps in the heading levels (for example, a level 3 header directly following a level 1 header)
+/// are handled gracefully. Headers which have no such preceding header become roots of the tree.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```rust
+/// use mtoc_parser::{headers, Outline};
+///
+/// let input = "# Title\n## Introduction\n## Body\n### Detail\n## Conclusion";
+/// let outline = headers(input).collect::<Outline>();
+///
+/// let title = &outline.nodes()[0];
+/// assert_eq!("Title", title.header().title());
+/// assert_eq!(3, title.children().len());
+/// assert_eq!("Detail", title.children()[1].children()[0].header().title());
+/// ```
+///
+/// An `Outline` can be flattened back into an `Iterator` of `Header`s in document order, which
+/// can be used with a [`Format`] implementation:
+///
+/// ```rust
+/// use mtoc_parser::{headers, Format, Formatter, Outline};
+/// use std::str;
+///
+/// let input = "# Title\n## Introduction\n### Detail\n## Body";
+/// let mut outline = headers(input).collect::<Outline>();
+/// outline.prune(2);
+///
+/// let mut output = Vec::new();
+/// Formatter::default().fmt(&mut output, outline.into_iter()).unwrap();
+///
+/// assert_eq!(
+///     "- [Title](#title)\n  * [Introduction](#introduction)\n  * [Body](#body)\n",
+///     str::from_utf8(&output).unwrap()
+/// );
+/// ```
+///
+/// [`Format`]: trait.Format.html
+/// [`Header`]: struct.Header.html
+/// [`Headers`]: struct.Headers.html
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outline<'a> {
+    nodes: Vec<Node<'a>>,
+}
+
+impl<'a> Outline<'a> {
+    /// Returns the root nodes of the tree.
+    pub fn nodes(&self) -> &[Node<'a>] {
+        &self.nodes
+    }
+
+    /// Returns `true` if the tree contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an `Iterator` over references to all [`Header`]s in the tree, in document order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Outline};
+    /// let outline = headers("# One\n## Two\n# Three").collect::<Outline>();
+    /// let titles = outline.iter().map(|h| h.title()).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(vec!["One", "Two", "Three"], titles);
+    /// ```
+    ///
+    /// [`Header`]: struct.Header.html
+    pub fn iter(&self) -> OutlineIter<'_, 'a> {
+        OutlineIter {
+            stack: vec![self.nodes.iter()],
+        }
+    }
+
+    /// Returns the node whose header has the given anchor link, if any.
+    ///
+    /// The anchor is matched against [`Header::anchor`], including its leading `#`. The returned
+    /// node holds the entire subtree below the header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Outline};
+    /// let outline = headers("# One\n## Two\n### Three\n# Four").collect::<Outline>();
+    /// let node = outline.find("#two").unwrap();
+    ///
+    /// assert_eq!("Two", node.header().title());
+    /// assert_eq!("Three", node.children()[0].header().title());
+    /// assert!(outline.find("#five").is_none());
+    /// ```
+    ///
+    /// [`Header::anchor`]: struct.Header.html#method.anchor
+    pub fn find(&self, anchor: &str) -> Option<&Node<'a>> {
+        find_node(&self.nodes, anchor)
+    }
+
+    /// Removes all nodes which are nested deeper than the given depth.
+    ///
+    /// The root nodes have a depth of `1`, their children a depth of `2`, and so on. Note that
+    /// the depth of a node is not necessarily related to the level of its header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Outline};
+    /// let mut outline = headers("# One\n## Two\n### Three").collect::<Outline>();
+    /// outline.prune(2);
+    ///
+    /// assert_eq!(2, outline.iter().count());
+    /// ```
+    pub fn prune(&mut self, depth: usize) {
+        prune_nodes(&mut self.nodes, depth);
+    }
+
+    /// Retains only the nodes whose header satisfies the predicate.
+    ///
+    /// When a node is removed, its entire subtree is removed with it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use mtoc_parser::{headers, Outline};
+    /// let mut outline = headers("# One\n## Changelog\n### v1\n## Two").collect::<Outline>();
+    /// outline.retain(|h| h.title() != "Changelog");
+    ///
+    /// let titles = outline.iter().map(|h| h.title()).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(vec!["One", "Two"], titles);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Header<'a>) -> bool,
+    {
+        retain_nodes(&mut self.nodes, &mut f);
+    }
+}
+
+impl<'a> FromIterator<Header<'a>> for Outline<'a> {
+    fn from_iter<I: IntoIterator<Item = Header<'a>>>(iter: I) -> Self {
+        let mut nodes = Vec::new();
+        // The chain of nodes from a root down to the most recent node, each of which may still
+        // receive children
+        let mut open: Vec<Node<'a>> = Vec::new();
+
+        for header in iter {
+            while let Some(node) = open.last() {
+                if node.header.level() < header.level() {
+                    break;
+                }
+                close_node(&mut open, &mut nodes);
+            }
+            open.push(Node {
+                header,
+                children: Vec::new(),
+            });
+        }
+        while !open.is_empty() {
+            close_node(&mut open, &mut nodes);
+        }
+
+        Outline { nodes }
+    }
+}
+
+impl<'a> IntoIterator for Outline<'a> {
+    type Item = Header<'a>;
+    type IntoIter = OutlineIntoIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        OutlineIntoIter {
+            stack: vec![self.nodes.into_iter()],
+        }
+    }
+}
+
+impl<'o, 'a> IntoIterator for &'o Outline<'a> {
+    type Item = &'o Header<'a>;
+    type IntoIter = OutlineIter<'o, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A node in an [`Outline`], holding a [`Header`] and its nested child nodes.
+///
+/// [`Header`]: struct.Header.html
+/// [`Outline`]: struct.Outline.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node<'a> {
+    header: Header<'a>,
+    children: Vec<Node<'a>>,
+}
+
+impl<'a> Node<'a> {
+    /// Returns the header of this node.
+    pub fn header(&self) -> &Header<'a> {
+        &self.header
+    }
+
+    /// Returns the child nodes of this node.
+    pub fn children(&self) -> &[Node<'a>] {
+        &self.children
+    }
+
+    /// Returns an `Iterator` over references to this node's [`Header`] followed by all of its
+    /// descendant `Header`s, in document order.
+    ///
+    /// [`Header`]: struct.Header.html
+    pub fn iter(&self) -> OutlineIter<'_, 'a> {
+        OutlineIter {
+            stack: vec![slice::from_ref(self).iter()],
+        }
+    }
+
+    /// Consumes this node, returning its header and dropping all of its children.
+    pub fn into_header(self) -> Header<'a> {
+        self.header
+    }
+}
+
+impl<'a> IntoIterator for Node<'a> {
+    type Item = Header<'a>;
+    type IntoIter = OutlineIntoIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        OutlineIntoIter {
+            stack: vec![vec![self].into_iter()],
+        }
+    }
+}
+
+/// An `Iterator` over references to the [`Header`]s of an [`Outline`], in document order.
+///
+/// This `struct` is created by the [`iter`] method on [`Outline`] and [`Node`].
+///
+/// [`Header`]: struct.Header.html
+/// [`Node`]: struct.Node.html
+/// [`Outline`]: struct.Outline.html
+/// [`iter`]: struct.Outline.html#method.iter
+#[derive(Debug)]
+pub struct OutlineIter<'o, 'a> {
+    stack: Vec<slice::Iter<'o, Node<'a>>>,
+}
+
+impl<'o, 'a> Iterator for OutlineIter<'o, 'a> {
+    type Item = &'o Header<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(node) => {
+                    self.stack.push(node.children.iter());
+                    return Some(&node.header);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// An `Iterator` which flattens an [`Outline`] into its [`Header`]s, in document order.
+///
+/// This `struct` is created by the `into_iter` method on [`Outline`] and [`Node`].
+///
+/// [`Header`]: struct.Header.html
+/// [`Node`]: struct.Node.html
+/// [`Outline`]: struct.Outline.html
+#[derive(Debug)]
+pub struct OutlineIntoIter<'a> {
+    stack: Vec<vec::IntoIter<Node<'a>>>,
+}
+
+impl<'a> Iterator for OutlineIntoIter<'a> {
+    type Item = Header<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(Node { header, children }) => {
+                    self.stack.push(children.into_iter());
+                    return Some(header);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Pops the most recent open node, adding it to its parent node or to the roots.
+fn close_node<'a>(open: &mut Vec<Node<'a>>, roots: &mut Vec<Node<'a>>) {
+    let node = open.pop().expect("open nodes should not be empty");
+    match open.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+fn find_node<'o, 'a>(nodes: &'o [Node<'a>], anchor: &str) -> Option<&'o Node<'a>> {
+    nodes.iter().find_map(|node| {
+        if node.header.anchor() == anchor {
+            Some(node)
+        } else {
+            find_node(&node.children, anchor)
+        }
+    })
+}
+
+fn prune_nodes(nodes: &mut Vec<Node<'_>>, depth: usize) {
+    if depth == 0 {
+        nodes.clear();
+    } else {
+        for node in nodes.iter_mut() {
+            prune_nodes(&mut node.children, depth - 1);
+        }
+    }
+}
+
+fn retain_nodes<'a, F>(nodes: &mut Vec<Node<'a>>, f: &mut F)
+where
+    F: FnMut(&Header<'a>) -> bool,
+{
+    nodes.retain(|node| f(&node.header));
+    for node in nodes.iter_mut() {
+        retain_nodes(&mut node.children, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headers;
+
+    const MD: &str =
+        "# Title\n## Introduction\n## Body\n### Detail\n#### Minutiae\n### Detail\n## Conclusion";
+
+    fn titles<'o>(nodes: &'o [Node<'_>]) -> Vec<&'o str> {
+        nodes.iter().map(|n| n.header().title()).collect()
+    }
+
+    #[test]
+    fn empty() {
+        let outline = headers("No headers here.").collect::<Outline>();
+
+        assert!(outline.is_empty());
+        assert_eq!(None, outline.iter().next());
+        assert_eq!(None, outline.into_iter().next());
+    }
+
+    #[test]
+    fn tree_structure() {
+        let outline = headers(MD).collect::<Outline>();
+
+        assert_eq!(vec!["Title"], titles(outline.nodes()));
+        let title = &outline.nodes()[0];
+        assert_eq!(
+            vec!["Introduction", "Body", "Conclusion"],
+            titles(title.children())
+        );
+        let body = &title.children()[1];
+        assert_eq!(vec!["Detail", "Detail"], titles(body.children()));
+        assert_eq!(vec!["Minutiae"], titles(body.children()[0].children()));
+        assert!(body.children()[1].children().is_empty());
+    }
+
+    #[test]
+    fn multiple_roots() {
+        let outline = headers("# One\n## One.One\n# Two\n# Three").collect::<Outline>();
+
+        assert_eq!(vec!["One", "Two", "Three"], titles(outline.nodes()));
+    }
+
+    #[test]
+    fn level_gaps() {
+        let outline = headers("# One\n### Skipped\n## Two\n#### Deep").collect::<Outline>();
+
+        let one = &outline.nodes()[0];
+        assert_eq!(vec!["Skipped", "Two"], titles(one.children()));
+        assert_eq!(vec!["Deep"], titles(one.children()[1].children()));
+    }
+
+    #[test]
+    fn leading_deeper_levels_become_roots() {
+        let outline = headers("### Three\n## Two\n### Nested\n# One").collect::<Outline>();
+
+        assert_eq!(vec!["Three", "Two", "One"], titles(outline.nodes()));
+        assert_eq!(vec!["Nested"], titles(outline.nodes()[1].children()));
+    }
+
+    #[test]
+    fn iter_in_document_order() {
+        let outline = headers(MD).collect::<Outline>();
+        let anchors = outline.iter().map(Header::anchor).collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "#title",
+                "#introduction",
+                "#body",
+                "#detail",
+                "#minutiae",
+                "#detail-1",
+                "#conclusion"
+            ],
+            anchors
+        );
+    }
+
+    #[test]
+    fn into_iter_round_trips() {
+        let outline = headers(MD).collect::<Outline>();
+
+        assert_eq!(
+            headers(MD).collect::<Vec<_>>(),
+            outline.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn find_subtree() {
+        let outline = headers(MD).collect::<Outline>();
+        let node = outline.find("#body").unwrap();
+
+        assert_eq!(
+            vec!["Body", "Detail", "Minutiae", "Detail"],
+            node.iter().map(Header::title).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "Minutiae",
+            outline.find("#minutiae").unwrap().header().title()
+        );
+        assert_eq!(None, outline.find("body"));
+    }
+
+    #[test]
+    fn node_into_iter() {
+        let outline = headers(MD).collect::<Outline>();
+        let node = outline.find("#detail").unwrap().clone();
+
+        assert_eq!(
+            vec!["Detail".to_string(), "Minutiae".to_string()],
+            node.into_iter().map(Header::into_title).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn prune() {
+        let mut outline = headers(MD).collect::<Outline>();
+        outline.prune(2);
+
+        assert_eq!(
+            vec!["Title", "Introduction", "Body", "Conclusion"],
+            outline.iter().map(Header::title).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn prune_to_nothing() {
+        let mut outline = headers(MD).collect::<Outline>();
+        outline.prune(0);
+
+        assert!(outline.is_empty());
+    }
+
+    #[test]
+    fn retain() {
+        let mut outline = headers(MD).collect::<Outline>();
+        outline.retain(|h| h.anchor() != "#detail");
+
+        assert_eq!(
+            vec!["Title", "Introduction", "Body", "Detail", "Conclusion"],
+            outline.iter().map(Header::title).collect::<Vec<_>>()
+        );
+        assert_eq!(None, outline.find("#minutiae"));
+    }
+}
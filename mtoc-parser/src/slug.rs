@@ -0,0 +1,326 @@
+// Copyright 2019 Fletcher Nichol and/or applicable contributors.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license (see <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
+// distributed except according to those terms.
+
+use crate::normalize;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A strategy for generating the anchor links of [`Header`]s.
+///
+/// Each renderer (GitHub, GitLab, Pandoc, etc.) has its own rules for turning the text of a
+/// heading into the id of its HTML element. A `Slugger` implements those rules in two parts: the
+/// [`slugify`] method normalizes the rendered text content of a heading into a slug, and the
+/// [`deduplicate`] method decides how a slug is made unique when it has already been used by an
+/// earlier heading (or is reserved by an explicit id in the document).
+///
+/// The text given to a `Slugger` has already been normalized to the Unicode normalization form
+/// of the [`Headers`] iterator. Explicit ids (such as `{#custom-id}`) are always used verbatim and
+/// never pass through a `Slugger`.
+///
+/// # Examples
+///
+/// A `Slugger` which only keeps ASCII alphanumeric characters and numbers duplicates with an
+/// underscore:
+///
+/// ```rust
+/// use mtoc_parser::{headers_with_slugger, Header, ParserOptions, Slugger};
+///
+/// struct AsciiSlugger;
+///
+/// impl Slugger for AsciiSlugger {
+///     fn slugify(&self, text: &str) -> String {
+///         text.split(|c: char| !c.is_ascii_alphanumeric())
+///             .filter(|word| !word.is_empty())
+///             .collect::<Vec<_>>()
+///             .join("-")
+///             .to_lowercase()
+///     }
+///
+///     fn deduplicate(&self, slug: &str, n: usize) -> String {
+///         format!("{}_{}", slug, n)
+///     }
+/// }
+///
+/// let markdown = "# Héllo, World!\n# Hello World\n";
+///
+/// let anchors = headers_with_slugger(markdown, ParserOptions::default(), AsciiSlugger)
+///     .map(Header::into_anchor)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(vec!["#h-llo-world", "#hello-world"], anchors);
+/// ```
+///
+/// [`Header`]: struct.Header.html
+/// [`Headers`]: struct.Headers.html
+/// [`deduplicate`]: #method.deduplicate
+/// [`slugify`]: #tymethod.slugify
+pub trait Slugger {
+    /// Returns the slug for the rendered text content of a heading, which need not be unique.
+    fn slugify(&self, text: &str) -> String;
+
+    /// Returns the `n`th candidate (starting at `1`) for a slug which is already in use.
+    ///
+    /// Candidates are tried in order until one is found which is not in use. The default
+    /// implementation appends `-n` to the slug, so that a second `Usage` heading becomes `usage-1`
+    /// and a third becomes `usage-2`.
+    fn deduplicate(&self, slug: &str, n: usize) -> String {
+        format!("{}-{}", slug, n)
+    }
+}
+
+impl<S: Slugger + ?Sized> Slugger for &S {
+    fn slugify(&self, text: &str) -> String {
+        (**self).slugify(text)
+    }
+
+    fn deduplicate(&self, slug: &str, n: usize) -> String {
+        (**self).deduplicate(slug, n)
+    }
+}
+
+impl<S: Slugger + ?Sized> Slugger for Box<S> {
+    fn slugify(&self, text: &str) -> String {
+        (**self).slugify(text)
+    }
+
+    fn deduplicate(&self, slug: &str, n: usize) -> String {
+        (**self).deduplicate(slug, n)
+    }
+}
+
+impl<S: Slugger + ?Sized> Slugger for Rc<S> {
+    fn slugify(&self, text: &str) -> String {
+        (**self).slugify(text)
+    }
+
+    fn deduplicate(&self, slug: &str, n: usize) -> String {
+        (**self).deduplicate(slug, n)
+    }
+}
+
+impl<S: Slugger + ?Sized> Slugger for Arc<S> {
+    fn slugify(&self, text: &str) -> String {
+        (**self).slugify(text)
+    }
+
+    fn deduplicate(&self, slug: &str, n: usize) -> String {
+        (**self).deduplicate(slug, n)
+    }
+}
+
+/// The default [`Slugger`], which generates anchor links the same way as GitHub.
+///
+/// The text is lowercased, spaces are replaced with dashes, and punctuation (including CJK
+/// punctuation) and Unicode emoji are removed. A duplicate slug is suffixed with `-1`, `-2`, etc.
+///
+/// # Examples
+///
+/// ```rust
+/// use mtoc_parser::{AnchorSlugger, Slugger};
+///
+/// assert_eq!("whats-new-in-v2", AnchorSlugger.slugify("What's new in v2?"));
+/// assert_eq!("usage-1", AnchorSlugger.deduplicate("usage", 1));
+/// ```
+///
+/// [`Slugger`]: trait.Slugger.html
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AnchorSlugger;
+
+impl Slugger for AnchorSlugger {
+    fn slugify(&self, text: &str) -> String {
+        normalize::slugify_text(text)
+    }
+}
+
+/// The slug rules of a Markdown renderer, which can be used as a [`Slugger`].
+///
+/// Each renderer generates the ids of its headings differently, so a table of contents should use
+/// the same flavor as the renderer of the document for its links to resolve.
+///
+/// # Examples
+///
+/// ```rust
+/// use mtoc_parser::{headers_with_slugger, Flavor, Header, ParserOptions};
+///
+/// let markdown = "# Step 1 -- Install\n# Step 1 -- Install\n";
+///
+/// let anchors = headers_with_slugger(markdown, ParserOptions::default(), Flavor::GitLab)
+///     .map(Header::into_anchor)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(vec!["#step-1-install", "#step-1-install-1"], anchors);
+/// ```
+///
+/// [`Slugger`]: trait.Slugger.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Flavor {
+    /// The heading ids generated by GitHub, as with the [`AnchorSlugger`]. For example:
+    ///
+    /// ```markdown
+    /// # Step 1 -- Install   => #step-1----install
+    /// # Step 1 -- Install   => #step-1----install-1
+    /// ```
+    ///
+    /// [`AnchorSlugger`]: struct.AnchorSlugger.html
+    GitHub,
+    /// The heading ids generated by GitLab, which collapses runs of dashes, prefixes an id which
+    /// is only made up of digits with `anchor-`, and keeps the text of emoji shortcodes while
+    /// removing Unicode emoji. For example:
+    ///
+    /// ```markdown
+    /// # Step 1 -- Install   => #step-1-install
+    /// # 2019                => #anchor-2019
+    /// # :rocket: Launch     => #rocket-launch
+    /// # 🚀 Launch           => #-launch
+    /// ```
+    GitLab,
+    /// The heading ids generated by Bitbucket, which prefixes every id with `markdown-header-`,
+    /// removes accents and any other characters which are not ASCII, collapses runs of dashes and
+    /// whitespace, and numbers a duplicate id with an underscore. For example:
+    ///
+    /// ```markdown
+    /// # Step 1 -- Install   => #markdown-header-step-1-install
+    /// # Step 1 -- Install   => #markdown-header-step-1-install_1
+    /// # Héllo, Wörld!       => #markdown-header-hello-world
+    /// ```
+    Bitbucket,
+    /// The identifiers generated by Pandoc's `auto_identifiers` extension, which keeps periods,
+    /// removes everything up to the first letter, and falls back to `section` for an identifier
+    /// which is left empty. A duplicate identifier is suffixed with `-1`, `-2`, etc. For example:
+    ///
+    /// ```markdown
+    /// # 3. Version 1.2      => #version-1.2
+    /// # Maître d'hôtel      => #maître-dhôtel
+    /// # 2019                => #section
+    /// # 2020                => #section-1
+    /// ```
+    Pandoc,
+    /// The identifiers generated by Pandoc's `auto_identifiers` extension together with its
+    /// `ascii_identifiers` extension, which also removes accents and any other characters which
+    /// are not ASCII. For example:
+    ///
+    /// ```markdown
+    /// # 3. Version 1.2      => #version-1.2
+    /// # Maître d'hôtel      => #maitre-dhotel
+    /// ```
+    PandocAscii,
+    /// The identifiers generated by Pandoc's `gfm_auto_identifiers` extension, which follows
+    /// GitHub more closely: numbers and repeated dashes are kept, but an identifier which is left
+    /// empty still falls back to `section`. For example:
+    ///
+    /// ```markdown
+    /// # 3. Version 1.2      => #3-version-12
+    /// # Maître d'hôtel      => #maître-dhôtel
+    /// # ???                 => #section
+    /// ```
+    PandocGfm,
+    /// The identifiers generated by kramdown, which renders GitHub Pages sites with Jekyll. It
+    /// removes everything up to the first ASCII letter, removes any letters which are not ASCII,
+    /// and falls back to `section` for an identifier which is left empty. A duplicate identifier
+    /// is suffixed with `-1`, `-2`, etc. For example:
+    ///
+    /// ```markdown
+    /// # 1. Café Menu        => #caf-menu
+    /// # snake_case          => #snakecase
+    /// # 日本語              => #section
+    /// # 2019                => #section-1
+    /// ```
+    Kramdown,
+}
+
+impl Default for Flavor {
+    fn default() -> Self {
+        Flavor::GitHub
+    }
+}
+
+impl Slugger for Flavor {
+    fn slugify(&self, text: &str) -> String {
+        use Flavor::*;
+
+        match self {
+            GitHub => normalize::slugify_text(text),
+            GitLab => normalize::slugify_gitlab(text),
+            Bitbucket => normalize::slugify_bitbucket(text),
+            Pandoc => normalize::slugify_pandoc(text, false),
+            PandocAscii => normalize::slugify_pandoc(text, true),
+            PandocGfm => normalize::slugify_pandoc_gfm(text),
+            Kramdown => normalize::slugify_kramdown(text),
+        }
+    }
+
+    fn deduplicate(&self, slug: &str, n: usize) -> String {
+        use Flavor::*;
+
+        match self {
+            Bitbucket => format!("{}_{}", slug, n),
+            _ => format!("{}-{}", slug, n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Underscores;
+
+    impl Slugger for Underscores {
+        fn slugify(&self, text: &str) -> String {
+            text.replace(" ", "_")
+        }
+    }
+
+    #[test]
+    fn anchor_slugger() {
+        assert_eq!("tom--jerry", AnchorSlugger.slugify("Tom & Jerry"));
+        assert_eq!("-launch", AnchorSlugger.slugify("🚀 Launch"));
+    }
+
+    #[test]
+    fn flavors() {
+        assert_eq!("tom--jerry", Flavor::GitHub.slugify("Tom & Jerry"));
+        assert_eq!("tom-jerry", Flavor::GitLab.slugify("Tom & Jerry"));
+        assert_eq!(
+            "markdown-header-tom-jerry",
+            Flavor::Bitbucket.slugify("Tom & Jerry")
+        );
+        assert_eq!("tom-jerry", Flavor::Pandoc.slugify("Tom & Jerry"));
+        assert_eq!("cafe", Flavor::PandocAscii.slugify("Café"));
+        assert_eq!("tom--jerry", Flavor::PandocGfm.slugify("Tom & Jerry"));
+        assert_eq!("tom--jerry", Flavor::Kramdown.slugify("Tom & Jerry"));
+        assert_eq!(Flavor::GitHub, Flavor::default());
+    }
+
+    #[test]
+    fn flavors_deduplicate() {
+        assert_eq!("usage-1", Flavor::GitHub.deduplicate("usage", 1));
+        assert_eq!("usage-2", Flavor::GitLab.deduplicate("usage", 2));
+        assert_eq!("usage_1", Flavor::Bitbucket.deduplicate("usage", 1));
+        assert_eq!("section-1", Flavor::Pandoc.deduplicate("section", 1));
+        assert_eq!("section-2", Flavor::Kramdown.deduplicate("section", 2));
+    }
+
+    #[test]
+    fn default_deduplicate() {
+        assert_eq!("a_b-1", Underscores.deduplicate("a_b", 1));
+        assert_eq!("a_b-12", Underscores.deduplicate("a_b", 12));
+    }
+
+    #[test]
+    fn pointers_delegate() {
+        let boxed: Box<dyn Slugger> = Box::new(Underscores);
+        let shared: Rc<dyn Slugger> = Rc::new(Underscores);
+
+        assert_eq!(
+            "a_b",
+            <&Underscores as Slugger>::slugify(&&Underscores, "a b")
+        );
+        assert_eq!("a_b", boxed.slugify("a b"));
+        assert_eq!("a_b", shared.slugify("a b"));
+    }
+}
@@ -5,10 +5,14 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be copied, modified, or
 // distributed except according to those terms.
 
-use crate::{Format, Formatter, Header};
-use pulldown_cmark::{Event, OffsetIter, Parser};
+use crate::{
+    front_matter, AnchorSlugger, Container, Emoji, Format, Formatter, Header, Normalization,
+    ParserOptions, Slugger,
+};
+use pulldown_cmark::{Event, OffsetIter};
 use std::io::{self, Cursor, Write};
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 const DEFAULT_BEGIN_MARKER: &str = "<!-- toc -->";
 const DEFAULT_END_MARKER: &str = "<!-- tocstop -->";
@@ -20,6 +24,11 @@ const DEFAULT_END_MARKER: &str = "<!-- tocstop -->";
 /// used as input in subsequent runs. The collection of [`Header`]s is consumed when [`write`] is
 /// called and can be repopulated by calling [`headers`] before calling `write` again.
 ///
+/// By default, the table of contents omits the document's title (that is, any level 1 headings)
+/// and promotes the remaining headings by one level. If the document starts with a front matter
+/// block which sets a `title`, then that is the document's title and every heading is included as
+/// it is. Any front matter block is otherwise skipped over.
+///
 /// # Examples
 ///
 /// Inserting a table of contents using the default start marker:
@@ -62,6 +71,25 @@ const DEFAULT_END_MARKER: &str = "<!-- tocstop -->";
 /// );
 /// ```
 ///
+/// Using a front matter title as the document's title:
+///
+/// ```rust
+/// use mtoc_parser::WriterBuilder;
+/// use std::str;
+///
+/// let input = "---\ntitle: Guide\n---\n<!-- toc -->\n\n# Intro\n## Setup\n";
+/// let mut output = Vec::new();
+///
+/// WriterBuilder::new(&input)
+///     .write(&mut output)
+///     .unwrap();
+///
+/// assert_eq!(
+///     "---\ntitle: Guide\n---\n<!-- toc -->\n\n- [Intro](#intro)\n  * [Setup](#setup)\n\n<!-- tocstop -->\n\n# Intro\n## Setup\n",
+///     str::from_utf8(&output).unwrap()
+/// );
+/// ```
+///
 /// [`Header`]: struct.Header.html
 /// [`headers`]: #fn.headers
 /// [`write`]: #fn.write
@@ -69,6 +97,11 @@ pub struct WriterBuilder<'a, 'c, 'd> {
     src: &'a str,
     begin_marker: &'c str,
     end_marker: &'d str,
+    options: ParserOptions,
+    excluded: Vec<Container>,
+    emoji: Emoji,
+    normalization: Normalization,
+    slugger: Arc<dyn Slugger + Send + Sync>,
 }
 
 impl<'a, 'c, 'd> WriterBuilder<'a, 'c, 'd> {
@@ -78,6 +111,11 @@ impl<'a, 'c, 'd> WriterBuilder<'a, 'c, 'd> {
             src,
             begin_marker: DEFAULT_BEGIN_MARKER,
             end_marker: DEFAULT_END_MARKER,
+            options: ParserOptions::default(),
+            excluded: Vec::new(),
+            emoji: Emoji::default(),
+            normalization: Normalization::default(),
+            slugger: Arc::new(AnchorSlugger),
         }
     }
 
@@ -123,6 +161,11 @@ impl<'a, 'c, 'd> WriterBuilder<'a, 'c, 'd> {
             src: self.src,
             begin_marker: self.begin_marker,
             end_marker: self.end_marker,
+            options: self.options,
+            excluded: self.excluded,
+            emoji: self.emoji,
+            normalization: self.normalization,
+            slugger: self.slugger,
             phantom: PhantomData,
             formatter,
             headers: None,
@@ -163,7 +206,7 @@ impl<'a, 'c, 'd> WriterBuilder<'a, 'c, 'd> {
     /// [module]: index.html
     pub fn headers<'b>(
         self,
-        headers: Box<dyn Iterator<Item = Header> + 'a>,
+        headers: Box<dyn Iterator<Item = Header<'a>> + 'a>,
     ) -> Writer<'a, 'b, 'c, 'd, Formatter<'b>> {
         self.formatter(Formatter::default()).headers(headers)
     }
@@ -228,6 +271,210 @@ impl<'a, 'c, 'd> WriterBuilder<'a, 'c, 'd> {
         self
     }
 
+    /// Sets the Markdown extensions which are enabled when parsing the document.
+    ///
+    /// By default, the document is parsed as strict CommonMark with no extensions enabled. The
+    /// extensions apply both to finding the markers and to the default table of contents
+    /// [`Header`]s, but have no effect on a custom iterator of `Header`s provided with
+    /// [`headers`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```rust
+    /// use mtoc_parser::{ParserOptions, WriterBuilder};
+    /// use std::str;
+    ///
+    /// let input = "<!-- toc -->\n\n# Title\n\nName |\n---\nPi |\n";
+    /// let mut output = Vec::new();
+    ///
+    /// WriterBuilder::new(&input)
+    ///     .parser_options(ParserOptions::gfm())
+    ///     .write(&mut output)
+    ///     .unwrap();
+    ///
+    /// // Without tables enabled, the table header would be read as a setext heading
+    /// assert!(!str::from_utf8(&output).unwrap().contains("(#name-)"));
+    /// ```
+    ///
+    /// [`Header`]: struct.Header.html
+    /// [`headers`]: #method.headers
+    pub fn parser_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Excludes any headings nested in the given kind of [`Container`] from the table of contents.
+    ///
+    /// By default, headings are included regardless of their container. This method can be called
+    /// more than once to exclude several kinds of container. Note that this has no effect on a
+    /// custom iterator of [`Header`]s provided with [`headers`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```rust
+    /// use mtoc_parser::{Container, WriterBuilder};
+    /// use std::str;
+    ///
+    /// let input = "<!-- toc -->\n\n# Title\n## Intro\n> ## Quoted\n";
+    /// let mut output = Vec::new();
+    ///
+    /// WriterBuilder::new(&input)
+    ///     .exclude(Container::BlockQuote)
+    ///     .write(&mut output)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     "<!-- toc -->\n\n- [Intro](#intro)\n\n<!-- tocstop -->\n\n# Title\n## Intro\n> ## Quoted\n",
+    ///     str::from_utf8(&output).unwrap()
+    /// );
+    /// ```
+    ///
+    /// [`Container`]: enum.Container.html
+    /// [`Header`]: struct.Header.html
+    /// [`headers`]: #method.headers
+    pub fn exclude(mut self, container: Container) -> Self {
+        if !self.excluded.contains(&container) {
+            self.excluded.push(container);
+        }
+        self
+    }
+
+    /// Sets how emoji are written in the titles of the table of contents.
+    ///
+    /// By default, emoji are kept as written. The anchor links are unaffected by this setting.
+    /// Note that this has no effect on a custom iterator of [`Header`]s provided with
+    /// [`headers`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```rust
+    /// use mtoc_parser::{Emoji, WriterBuilder};
+    /// use std::str;
+    ///
+    /// let input = "<!-- toc -->\n\n# Title\n## :rocket: Launch\n";
+    /// let mut output = Vec::new();
+    ///
+    /// WriterBuilder::new(&input)
+    ///     .emoji(Emoji::Unicode)
+    ///     .write(&mut output)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     "<!-- toc -->\n\n- [🚀 Launch](#rocket-launch)\n\n<!-- tocstop -->\n\n# Title\n## :rocket: Launch\n",
+    ///     str::from_utf8(&output).unwrap()
+    /// );
+    /// ```
+    ///
+    /// [`Header`]: struct.Header.html
+    /// [`headers`]: #method.headers
+    pub fn emoji(mut self, emoji: Emoji) -> Self {
+        self.emoji = emoji;
+        self
+    }
+
+    /// Sets the Unicode normalization form applied to the titles and anchor links of the table of
+    /// contents.
+    ///
+    /// By default, the NFC form is applied. Note that this has no effect on a custom iterator of
+    /// [`Header`]s provided with [`headers`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```rust
+    /// use mtoc_parser::{Normalization, WriterBuilder};
+    /// use std::str;
+    ///
+    /// let input = "<!-- toc -->\n\n# Title\n## Ｆｕｌｌ Width\n";
+    /// let mut output = Vec::new();
+    ///
+    /// WriterBuilder::new(&input)
+    ///     .normalization(Normalization::Nfkc)
+    ///     .write(&mut output)
+    ///     .unwrap();
+    ///
+    /// assert!(str::from_utf8(&output).unwrap().contains("- [Full Width](#full-width)\n"));
+    /// ```
+    ///
+    /// [`Header`]: struct.Header.html
+    /// [`headers`]: #method.headers
+    pub fn normalization(mut self, form: Normalization) -> Self {
+        self.normalization = form;
+        self
+    }
+
+    /// Sets the [`Slugger`] which generates the anchor links of the table of contents.
+    ///
+    /// The default is an [`AnchorSlugger`], which generates anchor links the same way as GitHub.
+    /// A [`Flavor`] generates anchor links the same way as another renderer, such as GitLab or
+    /// Bitbucket. Note that this has no effect on a custom iterator of [`Header`]s provided with
+    /// [`headers`]. The `Slugger` must be `Send` and `Sync` so that the builder remains `Send`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage, with the anchor links of Bitbucket:
+    ///
+    /// ```rust
+    /// use mtoc_parser::{Flavor, WriterBuilder};
+    /// use std::str;
+    ///
+    /// let input = "<!-- toc -->\n\n# Title\n## Getting Started\n";
+    /// let mut output = Vec::new();
+    ///
+    /// WriterBuilder::new(&input)
+    ///     .slugger(Flavor::Bitbucket)
+    ///     .write(&mut output)
+    ///     .unwrap();
+    ///
+    /// assert!(str::from_utf8(&output)
+    ///     .unwrap()
+    ///     .contains("- [Getting Started](#markdown-header-getting-started)\n"));
+    /// ```
+    ///
+    /// With a custom `Slugger`:
+    ///
+    /// ```rust
+    /// use mtoc_parser::{Slugger, WriterBuilder};
+    /// use std::str;
+    ///
+    /// struct Underscores;
+    ///
+    /// impl Slugger for Underscores {
+    ///     fn slugify(&self, text: &str) -> String {
+    ///         text.to_lowercase().replace(' ', "_")
+    ///     }
+    /// }
+    ///
+    /// let input = "<!-- toc -->\n\n# Title\n## Getting Started\n";
+    /// let mut output = Vec::new();
+    ///
+    /// WriterBuilder::new(&input)
+    ///     .slugger(Underscores)
+    ///     .write(&mut output)
+    ///     .unwrap();
+    ///
+    /// assert!(str::from_utf8(&output)
+    ///     .unwrap()
+    ///     .contains("- [Getting Started](#getting_started)\n"));
+    /// ```
+    ///
+    /// [`AnchorSlugger`]: struct.AnchorSlugger.html
+    /// [`Flavor`]: enum.Flavor.html
+    /// [`Header`]: struct.Header.html
+    /// [`Slugger`]: trait.Slugger.html
+    /// [`headers`]: #method.headers
+    pub fn slugger<S: Slugger + Send + Sync + 'static>(mut self, slugger: S) -> Self {
+        self.slugger = Arc::new(slugger);
+        self
+    }
+
     /// Writes the Markdown document with inlined table of contents to the provided 'writer'.
     ///
     /// Note that this method will consume the iterator of [`Header`]s and returns an internal type
@@ -242,13 +489,16 @@ impl<'a, 'c, 'd> WriterBuilder<'a, 'c, 'd> {
         writer: &mut W,
     ) -> io::Result<Writer<'a, 'b, 'c, 'd, Formatter<'b>>> {
         let formatter = Formatter::default();
-        let headers = crate::headers(self.src)
-            .filter(|h| h.level() > 1)
-            .map(Header::promote);
-
-        self.formatter(formatter)
-            .headers(Box::new(headers))
-            .write(writer)
+        let headers = default_headers(
+            self.src,
+            self.options,
+            &self.excluded,
+            self.emoji,
+            self.normalization,
+            Arc::clone(&self.slugger),
+        );
+
+        self.formatter(formatter).headers(headers).write(writer)
     }
 }
 
@@ -263,9 +513,14 @@ where
 {
     src: &'a str,
     formatter: F,
-    headers: Option<Box<dyn Iterator<Item = Header> + 'a>>,
+    headers: Option<Box<dyn Iterator<Item = Header<'a>> + 'a>>,
     begin_marker: &'c str,
     end_marker: &'d str,
+    options: ParserOptions,
+    excluded: Vec<Container>,
+    emoji: Emoji,
+    normalization: Normalization,
+    slugger: Arc<dyn Slugger + Send + Sync>,
     phantom: PhantomData<&'b F>,
 }
 
@@ -289,6 +544,11 @@ where
             headers: self.headers,
             begin_marker: self.begin_marker,
             end_marker: self.end_marker,
+            options: self.options,
+            excluded: self.excluded,
+            emoji: self.emoji,
+            normalization: self.normalization,
+            slugger: self.slugger,
             phantom: PhantomData,
             formatter,
         }
@@ -300,7 +560,7 @@ where
     ///
     /// [`Header`]: struct.Header.html
     /// [`headers`]: struct.WriterBuilder.html#method.headers
-    pub fn headers(mut self, headers: Box<dyn Iterator<Item = Header> + 'a>) -> Self {
+    pub fn headers(mut self, headers: Box<dyn Iterator<Item = Header<'a>> + 'a>) -> Self {
         self.headers = Some(headers);
         self
     }
@@ -325,6 +585,61 @@ where
         self
     }
 
+    /// Sets the Markdown extensions which are enabled when parsing the document.
+    ///
+    /// For more detail, see `WriterBuilder`'s [`parser_options`].
+    ///
+    /// [`parser_options`]: struct.WriterBuilder.html#method.parser_options
+    pub fn parser_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Excludes any headings nested in the given kind of [`Container`] from the table of contents.
+    ///
+    /// For more detail, see `WriterBuilder`'s [`exclude`].
+    ///
+    /// [`Container`]: enum.Container.html
+    /// [`exclude`]: struct.WriterBuilder.html#method.exclude
+    pub fn exclude(mut self, container: Container) -> Self {
+        if !self.excluded.contains(&container) {
+            self.excluded.push(container);
+        }
+        self
+    }
+
+    /// Sets how emoji are written in the titles of the table of contents.
+    ///
+    /// For more detail, see `WriterBuilder`'s [`emoji`].
+    ///
+    /// [`emoji`]: struct.WriterBuilder.html#method.emoji
+    pub fn emoji(mut self, emoji: Emoji) -> Self {
+        self.emoji = emoji;
+        self
+    }
+
+    /// Sets the Unicode normalization form applied to the titles and anchor links of the table of
+    /// contents.
+    ///
+    /// For more detail, see `WriterBuilder`'s [`normalization`].
+    ///
+    /// [`normalization`]: struct.WriterBuilder.html#method.normalization
+    pub fn normalization(mut self, form: Normalization) -> Self {
+        self.normalization = form;
+        self
+    }
+
+    /// Sets the [`Slugger`] which generates the anchor links of the table of contents.
+    ///
+    /// For more detail, see `WriterBuilder`'s [`slugger`].
+    ///
+    /// [`Slugger`]: trait.Slugger.html
+    /// [`slugger`]: struct.WriterBuilder.html#method.slugger
+    pub fn slugger<S: Slugger + Send + Sync + 'static>(mut self, slugger: S) -> Self {
+        self.slugger = Arc::new(slugger);
+        self
+    }
+
     /// Writes the Markdown document with inlined table of contents to the provided 'writer'.
     ///
     /// For more detail, see `WriterBuilder`'s [`write`].
@@ -334,20 +649,29 @@ where
         match self.headers {
             Some(_) => self.write_impl(writer),
             None => {
-                let headers = crate::headers(self.src)
-                    .filter(|h| h.level() > 1)
-                    .map(Header::promote);
-
-                self.headers(Box::new(headers)).write_impl(writer)
+                let headers = default_headers(
+                    self.src,
+                    self.options,
+                    &self.excluded,
+                    self.emoji,
+                    self.normalization,
+                    Arc::clone(&self.slugger),
+                );
+
+                self.headers(headers).write_impl(writer)
             }
         }
     }
 
     // pre-condition: `headers` *must* be set
     fn write_impl<W: Write>(mut self, writer: &mut W) -> io::Result<Self> {
-        let mut parser = Parser::new(self.src).into_offset_iter();
+        // Skip any front matter, so that a marker is only found in the document's body
+        let body = front_matter(self.src).map_or(0, |front_matter| front_matter.range().end);
+        let mut parser = self.options.parser(&self.src[body..]).into_offset_iter();
 
-        match begin_marker_eol_idx(self.src, self.begin_marker, &mut parser) {
+        match begin_marker_eol_idx(&self.src[body..], self.begin_marker, &mut parser)
+            .map(|idx| body + idx)
+        {
             Some(begin_marker_eol_idx) => {
                 io::copy(
                     &mut Cursor::new(self.src.get(..begin_marker_eol_idx).unwrap()),
@@ -359,7 +683,9 @@ where
                     .fmt(writer, self.headers.expect("headers should not be None"))?;
                 writer.write_all(b"\n")?;
 
-                match end_marker_sol_idx(self.src, self.end_marker, &mut parser) {
+                match end_marker_sol_idx(&self.src[body..], self.end_marker, &mut parser)
+                    .map(|idx| body + idx)
+                {
                     Some(end_marker_sol_idx) => {
                         io::copy(
                             &mut Cursor::new(self.src.get(end_marker_sol_idx..).unwrap()),
@@ -385,6 +711,34 @@ where
     }
 }
 
+/// Returns the default `Header`s for the table of contents of a document, which exclude the
+/// document's title (i.e. any level 1 headers) and are promoted by one level, unless the title is
+/// given by the document's front matter.
+fn default_headers<'a>(
+    src: &'a str,
+    options: ParserOptions,
+    excluded: &[Container],
+    emoji: Emoji,
+    normalization: Normalization,
+    slugger: Arc<dyn Slugger + Send + Sync>,
+) -> Box<dyn Iterator<Item = Header<'a>> + 'a> {
+    let headers = excluded.iter().fold(
+        crate::headers_with_slugger(src, options, slugger)
+            .emoji(emoji)
+            .normalization(normalization),
+        |headers, &container| headers.exclude(container),
+    );
+
+    if front_matter(src)
+        .and_then(|front_matter| front_matter.title())
+        .is_some()
+    {
+        Box::new(headers)
+    } else {
+        Box::new(headers.filter(|h| h.level() > 1).map(Header::promote))
+    }
+}
+
 fn begin_marker_eol_idx(src: &str, marker: &str, parser: &mut OffsetIter) -> Option<usize> {
     parser
         // Use the markdown parser to find the marker in HTML events only to exclude the same
@@ -444,6 +798,7 @@ fn end_marker_sol_idx(src: &str, marker: &str, parser: &mut OffsetIter) -> Optio
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Flavor;
     use indoc::indoc;
     use std::str;
 
@@ -993,10 +1348,10 @@ mod tests {
             }
 
             impl Format for Custom {
-                fn fmt<W, I>(&self, writer: &mut W, mut headers: I) -> io::Result<()>
+                fn fmt<'h, W, I>(&self, writer: &mut W, mut headers: I) -> io::Result<()>
                 where
                     W: Write,
-                    I: Iterator<Item = Header>,
+                    I: Iterator<Item = Header<'h>>,
                 {
                     headers.try_for_each(|h| {
                         writeln!(
@@ -1048,7 +1403,6 @@ mod tests {
                 str::from_utf8(&out).unwrap()
             );
         }
-
     }
 
     mod headers {
@@ -1199,5 +1553,165 @@ mod tests {
                 str::from_utf8(&out).unwrap()
             );
         }
+
+        #[test]
+        fn excluded_containers() {
+            let md = indoc!(
+                "<!-- toc -->
+
+                # Title
+                ## Intro
+                > ## Quoted
+                ## Usage
+                - ## Listed
+                "
+            );
+
+            let mut out = Vec::new();
+            WriterBuilder::new(md)
+                .formatter(Formatter::Numbers)
+                .exclude(Container::BlockQuote)
+                .exclude(Container::List)
+                .write(&mut out)
+                .unwrap();
+
+            assert_eq!(
+                indoc!(
+                    "<!-- toc -->
+
+                    1. [Intro](#intro)
+                    1. [Usage](#usage)
+
+                    <!-- tocstop -->
+
+                    # Title
+                    ## Intro
+                    > ## Quoted
+                    ## Usage
+                    - ## Listed
+                    "
+                ),
+                str::from_utf8(&out).unwrap()
+            );
+        }
+
+        #[test]
+        fn bitbucket_flavor() {
+            let md = indoc!(
+                "<!-- toc -->
+
+                # Title
+                ## Café Menu
+                ## Usage
+                ## Usage
+                "
+            );
+
+            let mut out = Vec::new();
+            WriterBuilder::new(md)
+                .slugger(Flavor::Bitbucket)
+                .write(&mut out)
+                .unwrap();
+
+            assert_eq!(
+                indoc!(
+                    "<!-- toc -->
+
+                    - [Café Menu](#markdown-header-cafe-menu)
+                    - [Usage](#markdown-header-usage)
+                    - [Usage](#markdown-header-usage_1)
+
+                    <!-- tocstop -->
+
+                    # Title
+                    ## Café Menu
+                    ## Usage
+                    ## Usage
+                    "
+                ),
+                str::from_utf8(&out).unwrap()
+            );
+        }
+
+        #[test]
+        fn builder_is_send() {
+            fn assert_send<T: Send>() {}
+
+            assert_send::<WriterBuilder<'_, '_, '_>>();
+        }
+
+        #[test]
+        fn skips_front_matter() {
+            let md = indoc!(
+                "---
+                layout: page
+                ---
+                <!-- toc -->
+
+                # Title
+                ## Intro
+                "
+            );
+
+            let mut out = Vec::new();
+            WriterBuilder::new(md).write(&mut out).unwrap();
+
+            assert_eq!(
+                indoc!(
+                    "---
+                    layout: page
+                    ---
+                    <!-- toc -->
+
+                    - [Intro](#intro)
+
+                    <!-- tocstop -->
+
+                    # Title
+                    ## Intro
+                    "
+                ),
+                str::from_utf8(&out).unwrap()
+            );
+        }
+
+        #[test]
+        fn front_matter_title_keeps_all_headers() {
+            let md = indoc!(
+                "+++
+                title = \"Guide\"
+                +++
+                <!-- toc -->
+
+                # Intro
+                ## Setup
+                # Usage
+                "
+            );
+
+            let mut out = Vec::new();
+            WriterBuilder::new(md).write(&mut out).unwrap();
+
+            assert_eq!(
+                indoc!(
+                    "+++
+                    title = \"Guide\"
+                    +++
+                    <!-- toc -->
+
+                    - [Intro](#intro)
+                      * [Setup](#setup)
+                    - [Usage](#usage)
+
+                    <!-- tocstop -->
+
+                    # Intro
+                    ## Setup
+                    # Usage
+                    "
+                ),
+                str::from_utf8(&out).unwrap()
+            );
+        }
     }
 }
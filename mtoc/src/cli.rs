@@ -6,7 +6,7 @@
 // distributed except according to those terms.
 
 use crate::Result;
-use mtoc_parser::Formatter;
+use mtoc_parser::{Container, Emoji, Flavor, Formatter, Normalization, ParserOptions};
 use std::convert::TryInto;
 use std::fs::File;
 use std::io::{BufReader, Read};
@@ -156,6 +156,93 @@ pub(crate) struct Args {
     )]
     format: CliFormat,
 
+    /// Sets the Markdown dialect used to parse the input.
+    ///
+    /// The 'commonmark' dialect is strict CommonMark with no extensions. The 'gfm' dialect is
+    /// GitHub Flavored Markdown, which enables tables, footnotes, strikethrough, and task lists.
+    /// Using the same dialect as the renderer of the document ensures that the table of contents
+    /// is derived from the headings as they will be rendered.
+    #[structopt(
+        short = "d",
+        long = "dialect",
+        rename_all = "screaming_snake_case",
+        raw(
+            possible_values = "&CliDialect::variants()",
+            default_value = "\"commonmark\""
+        )
+    )]
+    dialect: CliDialect,
+
+    /// Excludes headings nested in a container.
+    ///
+    /// By default, the table of contents includes headings nested in block quotes ('blockquote'),
+    /// list items ('list') and collapsible HTML <details> elements ('details'). Headings nested in
+    /// an excluded container are left out of the table of contents.
+    ///
+    /// This option can be used multiple times to exclude more than one kind of container.
+    #[structopt(
+        short = "x",
+        long = "exclude",
+        rename_all = "screaming_snake_case",
+        raw(possible_values = "&CliContainer::variants()", number_of_values = "1")
+    )]
+    exclude: Vec<CliContainer>,
+
+    /// Sets how emoji are written in the table of contents.
+    ///
+    /// Emoji in a heading can be written as a shortcode (such as ':rocket:') or as a Unicode emoji.
+    /// By default, emoji are kept as written ('keep'). Emoji shortcodes can instead be rendered as
+    /// Unicode emoji ('unicode'), or all emoji can be dropped ('drop'). The anchor links are
+    /// unaffected by this option.
+    #[structopt(
+        short = "E",
+        long = "emoji",
+        rename_all = "screaming_snake_case",
+        raw(possible_values = "&CliEmoji::variants()", default_value = "\"keep\"")
+    )]
+    emoji: CliEmoji,
+
+    /// Sets the Unicode normalization form of titles and anchor links.
+    ///
+    /// The same text can be typed as different sequences of Unicode characters, for example, an
+    /// 'é' can be a single character or an 'e' followed by a combining accent. Normalizing the
+    /// headings ensures that they have the same anchor links however they were typed. The
+    /// compatibility forms ('nfkc' and 'nfkd') also fold variants such as full-width letters into
+    /// their plain counterparts.
+    #[structopt(
+        short = "n",
+        long = "normalization",
+        rename_all = "screaming_snake_case",
+        raw(
+            possible_values = "&CliNormalization::variants()",
+            default_value = "\"nfc\""
+        )
+    )]
+    normalization: CliNormalization,
+
+    /// Sets the flavor of the anchor links.
+    ///
+    /// Each Markdown renderer generates the ids of its headings differently, so the anchor links
+    /// should be generated the same way as the renderer of the document. By default, anchor links
+    /// are generated the same way as GitHub ('github'). GitLab ('gitlab') also collapses runs of
+    /// dashes and prefixes an anchor link which is only made up of digits with 'anchor-'. Bitbucket
+    /// ('bitbucket') prefixes every anchor link with 'markdown-header-' and drops accents and any
+    /// other characters which are not ASCII. Pandoc ('pandoc') keeps periods, removes everything
+    /// up to the first letter and falls back to 'section', while 'pandoc_ascii' also drops accents
+    /// and other characters which are not ASCII, and 'pandoc_gfm' follows GitHub more closely.
+    /// Kramdown ('kramdown'), which renders GitHub Pages sites, removes everything up to the first
+    /// letter and any letters which are not ASCII, and falls back to 'section'.
+    #[structopt(
+        short = "F",
+        long = "flavor",
+        rename_all = "screaming_snake_case",
+        raw(
+            possible_values = "&CliFlavor::variants()",
+            default_value = "\"github\""
+        )
+    )]
+    flavor: CliFlavor,
+
     /// Sets a custom begin marker.
     ///
     /// The begin marker is used by mtoc to insert a new table of contents or to replace an
@@ -222,6 +309,31 @@ impl Args {
         Formatter::from(&self.format)
     }
 
+    /// Returns the `ParserOptions` for the selected Markdown dialect.
+    pub(crate) fn parser_options(&self) -> ParserOptions {
+        ParserOptions::from(&self.dialect)
+    }
+
+    /// Returns the kinds of `Container` whose nested headings are excluded.
+    pub(crate) fn excluded(&self) -> Vec<Container> {
+        self.exclude.iter().map(Container::from).collect()
+    }
+
+    /// Returns how emoji are written in the table of contents.
+    pub(crate) fn emoji(&self) -> Emoji {
+        Emoji::from(&self.emoji)
+    }
+
+    /// Returns the Unicode `Normalization` form of titles and anchor links.
+    pub(crate) fn normalization(&self) -> Normalization {
+        Normalization::from(&self.normalization)
+    }
+
+    /// Returns the `Flavor` of the anchor links.
+    pub(crate) fn flavor(&self) -> Flavor {
+        Flavor::from(&self.flavor)
+    }
+
     /// Returns whether or not the "in place" editing mode has been selected.
     ///
     /// If this mode has been selected, then the output target will be the same as the input
@@ -322,6 +434,129 @@ impl From<&CliFormat> for Formatter<'static> {
     }
 }
 
+arg_enum! {
+    /// The possible dialect values for the CLI.
+    #[derive(Debug)]
+    #[allow(non_camel_case_types)]
+    enum CliDialect {
+        commonmark,
+        gfm,
+    }
+}
+
+impl From<&CliDialect> for ParserOptions {
+    fn from(dialect: &CliDialect) -> ParserOptions {
+        use CliDialect::*;
+
+        match dialect {
+            commonmark => ParserOptions::new(),
+            gfm => ParserOptions::gfm(),
+        }
+    }
+}
+
+arg_enum! {
+    /// The possible container values for the CLI.
+    #[derive(Debug)]
+    #[allow(non_camel_case_types)]
+    enum CliContainer {
+        blockquote,
+        list,
+        details,
+    }
+}
+
+impl From<&CliContainer> for Container {
+    fn from(container: &CliContainer) -> Container {
+        use CliContainer::*;
+
+        match container {
+            blockquote => Container::BlockQuote,
+            list => Container::List,
+            details => Container::Details,
+        }
+    }
+}
+
+arg_enum! {
+    /// The possible emoji values for the CLI.
+    #[derive(Debug)]
+    #[allow(non_camel_case_types)]
+    enum CliEmoji {
+        keep,
+        unicode,
+        drop,
+    }
+}
+
+impl From<&CliEmoji> for Emoji {
+    fn from(emoji: &CliEmoji) -> Emoji {
+        use CliEmoji::*;
+
+        match emoji {
+            keep => Emoji::Keep,
+            unicode => Emoji::Unicode,
+            drop => Emoji::Drop,
+        }
+    }
+}
+
+arg_enum! {
+    /// The possible Unicode normalization form values for the CLI.
+    #[derive(Debug)]
+    #[allow(non_camel_case_types)]
+    enum CliNormalization {
+        nfc,
+        nfd,
+        nfkc,
+        nfkd,
+    }
+}
+
+impl From<&CliNormalization> for Normalization {
+    fn from(normalization: &CliNormalization) -> Normalization {
+        use CliNormalization::*;
+
+        match normalization {
+            nfc => Normalization::Nfc,
+            nfd => Normalization::Nfd,
+            nfkc => Normalization::Nfkc,
+            nfkd => Normalization::Nfkd,
+        }
+    }
+}
+
+arg_enum! {
+    /// The possible anchor link flavor values for the CLI.
+    #[derive(Debug)]
+    #[allow(non_camel_case_types)]
+    enum CliFlavor {
+        github,
+        gitlab,
+        bitbucket,
+        pandoc,
+        pandoc_ascii,
+        pandoc_gfm,
+        kramdown,
+    }
+}
+
+impl From<&CliFlavor> for Flavor {
+    fn from(flavor: &CliFlavor) -> Flavor {
+        use CliFlavor::*;
+
+        match flavor {
+            github => Flavor::GitHub,
+            gitlab => Flavor::GitLab,
+            bitbucket => Flavor::Bitbucket,
+            pandoc => Flavor::Pandoc,
+            pandoc_ascii => Flavor::PandocAscii,
+            pandoc_gfm => Flavor::PandocGfm,
+            kramdown => Flavor::Kramdown,
+        }
+    }
+}
+
 /// Read and return the contents of the standard input stream as a `String`.
 ///
 /// # Errors
@@ -50,13 +50,21 @@ fn try_main() -> Result<()> {
 
     let buf = args.input_string()?;
 
-    let mut builder = WriterBuilder::new(&buf).formatter(args.formatter());
+    let mut builder = WriterBuilder::new(&buf)
+        .formatter(args.formatter())
+        .parser_options(args.parser_options())
+        .emoji(args.emoji())
+        .normalization(args.normalization())
+        .slugger(args.flavor());
     if let Some(marker) = args.begin_marker() {
         builder = builder.begin_marker(marker);
     }
     if let Some(marker) = args.end_marker() {
         builder = builder.end_marker(marker);
     }
+    for container in args.excluded() {
+        builder = builder.exclude(container);
+    }
 
     if args.check_mode() {
         info!("check mode");
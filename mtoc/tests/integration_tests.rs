@@ -180,3 +180,287 @@ Diff in simple-new.md at line 2:
 ",
         );
 }
+
+#[test]
+fn check_excluded_containers_identical() {
+    mtoc()
+        .arg("--check")
+        .arg("--exclude")
+        .arg("blockquote")
+        .arg("--exclude")
+        .arg("list")
+        .arg("--exclude")
+        .arg("details")
+        .arg("containers.md")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_included_containers_differs() {
+    mtoc()
+        .arg("--check")
+        .arg("--exclude")
+        .arg("blockquote")
+        .arg("containers.md")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(
+            "\
+Diff in containers.md at line 4:
+ 
+ - [Introduction](#introduction)
+ - [Body](#body)
++- [Listed](#listed)
++  * [Collapsed](#collapsed)
+ - [Conclusion](#conclusion)
+ 
+ <!-- tocstop -->
+",
+        );
+}
+
+#[test]
+fn check_gfm_dialect_identical() {
+    mtoc()
+        .arg("--check")
+        .arg("--dialect")
+        .arg("gfm")
+        .arg("dialect.md")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_commonmark_dialect_differs() {
+    mtoc()
+        .arg("--check")
+        .arg("dialect.md")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(
+            "\
+Diff in dialect.md at line 3:
+ <!-- toc -->
+ 
+ - [Results](#results)
++- [Name \\|](#name-)
+ 
+ <!-- tocstop -->
+ 
+",
+        );
+}
+
+#[test]
+fn check_unicode_emoji_identical() {
+    mtoc()
+        .arg("--check")
+        .arg("--emoji")
+        .arg("unicode")
+        .arg("emoji.md")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_kept_emoji_differs() {
+    mtoc()
+        .arg("--check")
+        .arg("emoji.md")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(
+            "\
+Diff in emoji.md at line 2:
+ 
+ <!-- toc -->
+ 
+-- [🚀 Quick start](#rocket-quick-start)
++- [:rocket: Quick start](#rocket-quick-start)
+ - [✨ Features](#-features)
+ 
+ <!-- tocstop -->
+",
+        );
+}
+
+#[test]
+fn check_nfkc_normalization_identical() {
+    mtoc()
+        .arg("--check")
+        .arg("--normalization")
+        .arg("nfkc")
+        .arg("normalization.md")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_nfc_normalization_differs() {
+    mtoc()
+        .arg("--check")
+        .arg("normalization.md")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(
+            "\
+Diff in normalization.md at line 2:
+ 
+ <!-- toc -->
+ 
+-- [API Guide](#api-guide)
++- [ＡＰＩ Ｇｕｉｄｅ](#ａｐｉ-ｇｕｉｄｅ)
+ 
+ <!-- tocstop -->
+ 
+",
+        );
+}
+
+#[test]
+fn check_gitlab_flavor_identical() {
+    mtoc()
+        .arg("--check")
+        .arg("--flavor")
+        .arg("gitlab")
+        .arg("flavor.md")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_github_flavor_differs() {
+    mtoc()
+        .arg("--check")
+        .arg("flavor.md")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(
+            "\
+Diff in flavor.md at line 2:
+ 
+ <!-- toc -->
+ 
+-- [Step 1 -- Install](#step-1-install)
+-- [2019](#anchor-2019)
++- [Step 1 -- Install](#step-1----install)
++- [2019](#2019)
+ 
+ <!-- tocstop -->
+ 
+",
+        );
+}
+
+#[test]
+fn check_bitbucket_flavor_identical() {
+    mtoc()
+        .arg("--check")
+        .arg("--flavor")
+        .arg("bitbucket")
+        .arg("bitbucket.md")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_pandoc_flavor_identical() {
+    mtoc()
+        .arg("--check")
+        .arg("--flavor")
+        .arg("pandoc")
+        .arg("pandoc.md")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_pandoc_gfm_flavor_differs() {
+    mtoc()
+        .arg("--check")
+        .arg("--flavor")
+        .arg("pandoc_gfm")
+        .arg("pandoc.md")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(
+            "\
+Diff in pandoc.md at line 2:
+ 
+ <!-- toc -->
+ 
+-- [1. Getting Started](#getting-started)
+-- [Version 1.2](#version-1.2)
+-- [2019](#section)
+-- [2020](#section-1)
++- [1. Getting Started](#1-getting-started)
++- [Version 1.2](#version-12)
++- [2019](#2019)
++- [2020](#2020)
+ 
+ <!-- tocstop -->
+ 
+",
+        );
+}
+
+#[test]
+fn check_kramdown_flavor_identical() {
+    mtoc()
+        .arg("--check")
+        .arg("--flavor")
+        .arg("kramdown")
+        .arg("kramdown.md")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_github_flavor_kramdown_differs() {
+    mtoc()
+        .arg("--check")
+        .arg("kramdown.md")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(
+            "\
+Diff in kramdown.md at line 2:
+ 
+ <!-- toc -->
+ 
+-- [1. Getting Started](#getting-started)
+-- [Café Menu](#caf-menu)
+-- [Getting Started](#getting-started-1)
++- [1. Getting Started](#1-getting-started)
++- [Café Menu](#café-menu)
++- [Getting Started](#getting-started)
+ 
+ <!-- tocstop -->
+ 
+",
+        );
+}